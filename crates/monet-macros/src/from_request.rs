@@ -0,0 +1,249 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{
+    Data, DeriveInput, Fields, GenericArgument, Ident, LitStr, PathArguments, Type,
+    spanned::Spanned,
+};
+
+use crate::ident_crate;
+
+/// Where a field of a `#[derive(FromRequest)]` struct is extracted from.
+enum Source {
+    Path,
+    Query,
+    Header(LitStr),
+    State,
+    Json,
+}
+
+struct Field<'a> {
+    ident: &'a Ident,
+    ty: &'a Type,
+    source: Source,
+}
+
+pub(crate) fn generate(input: DeriveInput) -> syn::Result<TokenStream> {
+    let this = ident_crate();
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => return Err(not_named_struct(&input)),
+        },
+        _ => return Err(not_named_struct(&input)),
+    };
+    let fields = fields
+        .iter()
+        .map(|field| {
+            let ident = field.ident.as_ref().expect("named fields have an ident");
+            let source = parse_source(field)?;
+            if matches!(source, Source::State) && app_state_inner(&field.ty).is_none() {
+                return Err(syn::Error::new_spanned(
+                    &field.ty,
+                    "a `#[from(state)]` field must be an `AppState<T>`",
+                ));
+            }
+            Ok(Field {
+                ident,
+                ty: &field.ty,
+                source,
+            })
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    let mut body_fields = fields.iter().filter(|f| matches!(f.source, Source::Json));
+    let body_field = body_fields.next();
+    if let Some(extra) = body_fields.next() {
+        return Err(syn::Error::new_spanned(
+            extra.ident,
+            "only one field can be extracted from the request body",
+        ));
+    }
+
+    let vars = fields
+        .iter()
+        .map(|f| format_ident!("__monet_field_{}", f.ident))
+        .collect::<Vec<_>>();
+    let idents = fields.iter().map(|f| f.ident).collect::<Vec<_>>();
+
+    // The body is consumed last, once every other field has been read from the request.
+    let ordered = fields
+        .iter()
+        .zip(&vars)
+        .filter(|(f, _)| !matches!(f.source, Source::Json))
+        .chain(
+            fields
+                .iter()
+                .zip(&vars)
+                .filter(|(f, _)| matches!(f.source, Source::Json)),
+        );
+    let extract = ordered.map(|(field, var)| {
+        let expr = extract_expr(&this, field);
+        let label = field.ident.to_string();
+        quote! {
+            let #var = match #expr {
+                Ok(value) => Some(value),
+                Err(err) => {
+                    __monet_errors.push(#label, err);
+                    None
+                }
+            };
+        }
+    });
+
+    let required_state = fields
+        .iter()
+        .filter(|f| matches!(f.source, Source::State))
+        .map(|f| {
+            let ty = app_state_inner(f.ty).expect("checked when parsing the fields");
+            quote!(keys.push(#this::request::StateKey::of::<#ty>());)
+        })
+        .collect::<Vec<_>>();
+    let required_state = (!required_state.is_empty()).then(|| {
+        quote! {
+            fn required_state(keys: &mut ::std::vec::Vec<#this::request::StateKey>) {
+                #(#required_state)*
+            }
+        }
+    });
+
+    let body = quote! {
+        let mut __monet_errors = #this::error::FieldErrors::default();
+        #(#extract)*
+        match (#(#vars,)*) {
+            (#(Some(#vars),)*) => Ok(Self { #(#idents: #vars),* }),
+            _ => Err(__monet_errors),
+        }
+    };
+
+    let extractor = match body_field {
+        Some(_) => quote! {
+            impl #impl_generics #this::FromRequest for #name #ty_generics #where_clause {
+                type Rejection = #this::error::FieldErrors;
+
+                async fn from_request(req: #this::Request) -> Result<Self, Self::Rejection> {
+                    #body
+                }
+
+                #required_state
+            }
+        },
+        None => quote! {
+            impl #impl_generics #this::FromRequestParts for #name #ty_generics #where_clause {
+                type Rejection = #this::error::FieldErrors;
+
+                async fn from_request_parts(
+                    req: &mut #this::Request,
+                ) -> Result<Self, Self::Rejection> {
+                    #body
+                }
+
+                #required_state
+            }
+        },
+    };
+
+    Ok(quote! {
+        #[#this::async_trait(?Send)]
+        #extractor
+    })
+}
+
+fn not_named_struct(input: &DeriveInput) -> syn::Error {
+    syn::Error::new_spanned(
+        &input.ident,
+        "#[derive(FromRequest)] only supports structs with named fields",
+    )
+}
+
+fn parse_source(field: &syn::Field) -> syn::Result<Source> {
+    let mut attrs = field.attrs.iter().filter(|a| a.path().is_ident("from"));
+    let Some(attr) = attrs.next() else {
+        return Err(syn::Error::new_spanned(
+            field,
+            "missing `#[from(...)]` attribute, expected one of `path`, `query`, \
+             `header = \"name\"`, `state` or `json`",
+        ));
+    };
+    if let Some(extra) = attrs.next() {
+        return Err(syn::Error::new_spanned(
+            extra,
+            "a field can only be extracted from one source",
+        ));
+    }
+
+    let mut source = None;
+    attr.parse_nested_meta(|meta| {
+        if source.is_some() {
+            return Err(meta.error("a field can only be extracted from one source"));
+        }
+        source = Some(if meta.path.is_ident("path") {
+            Source::Path
+        } else if meta.path.is_ident("query") {
+            Source::Query
+        } else if meta.path.is_ident("header") {
+            Source::Header(meta.value()?.parse()?)
+        } else if meta.path.is_ident("state") {
+            Source::State
+        } else if meta.path.is_ident("json") {
+            Source::Json
+        } else {
+            return Err(meta.error(
+                "unknown source, expected one of `path`, `query`, `header = \"name\"`, \
+                 `state` or `json`",
+            ));
+        });
+        Ok(())
+    })?;
+
+    source.ok_or_else(|| syn::Error::new(attr.span(), "missing the source of the field"))
+}
+
+fn extract_expr(this: &Ident, field: &Field<'_>) -> TokenStream {
+    let ty = field.ty;
+    match &field.source {
+        Source::Path => quote!(req.path::<#ty>().map(|path| path.0)),
+        Source::Query => quote!(req.query::<#ty>().map(|query| query.0)),
+        Source::Header(name) => match option_inner(ty) {
+            Some(inner) => {
+                quote!(#this::extract::__private::optional_header::<#inner>(&req, #name))
+            }
+            None => quote!(#this::extract::__private::header::<#ty>(&req, #name)),
+        },
+        Source::State => {
+            let inner = app_state_inner(ty).expect("checked when parsing the fields");
+            quote!(#this::extract::__private::app_state::<#inner>(&req))
+        }
+        Source::Json => quote!(req.into_json::<#ty>().await.map(|json| json.0)),
+    }
+}
+
+/// `T` if `ty` is written `Option<T>`.
+fn option_inner(ty: &Type) -> Option<&Type> {
+    generic_inner(ty, "Option")
+}
+
+/// `T` if `ty` is written `AppState<T>`.
+fn app_state_inner(ty: &Type) -> Option<&Type> {
+    generic_inner(ty, "AppState")
+}
+
+/// `T` if `ty` is written `<wrapper><T>`.
+fn generic_inner<'a>(ty: &'a Type, wrapper: &str) -> Option<&'a Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if segment.ident != wrapper {
+        return None;
+    }
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) => match args.args.first()? {
+            GenericArgument::Type(inner) => Some(inner),
+            _ => None,
+        },
+        _ => None,
+    }
+}
@@ -1,3 +1,130 @@
-use proc_macro2::{Span, TokenStream};
-use quote::{ToTokens, quote};
-use syn::{Ident, ImplItem, Item, Pat, ReturnType, Signature, Type};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, quote_spanned};
+use syn::{ItemFn, Type, spanned::Spanned};
+
+use crate::{
+    ident_crate,
+    util::{InputType, parse_input_type},
+};
+
+pub(crate) fn generate(item_fn: ItemFn) -> syn::Result<TokenStream> {
+    let this = ident_crate();
+    let vis = &item_fn.vis;
+    let sig = &item_fn.sig;
+    let body = &item_fn.block;
+    let name = &sig.ident;
+
+    if !sig.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &sig.generics,
+            "#[handler] functions cannot be generic",
+        ));
+    }
+
+    let (docs, attrs): (Vec<_>, Vec<_>) = item_fn
+        .attrs
+        .iter()
+        .filter(|a| !a.path().is_ident("handler"))
+        .partition(|a| a.path().is_ident("doc"));
+
+    let types = extractor_types(sig)?;
+    let args = (0..types.len())
+        .map(|i| format_ident!("__monet_arg{}", i))
+        .collect::<Vec<_>>();
+
+    let mut extract = Vec::with_capacity(types.len());
+    let mut required_state = Vec::with_capacity(types.len());
+    if let Some((last, init)) = types.split_last() {
+        for (arg, ty) in args.iter().zip(init) {
+            let from_parts = quote_spanned! {ty.span()=>
+                <#ty as #this::FromRequestParts>::from_request_parts
+            };
+            extract.push(quote! {
+                let #arg = match #from_parts(&mut req).await {
+                    Ok(value) => value,
+                    Err(rejection) => return #this::IntoResponse::into_response(rejection),
+                };
+            });
+            required_state.push(quote_spanned! {ty.span()=>
+                <#ty as #this::FromRequestParts>::required_state(&mut keys);
+            });
+        }
+
+        let arg = args.last().expect("one argument per type");
+        let from_request = quote_spanned! {last.span()=>
+            <#last as #this::FromRequest<_>>::from_request
+        };
+        extract.push(quote! {
+            let #arg = match #from_request(req).await {
+                Ok(value) => value,
+                Err(rejection) => return #this::IntoResponse::into_response(rejection),
+            };
+        });
+        required_state.push(quote_spanned! {last.span()=>
+            <#last as #this::FromRequest<_>>::required_state(&mut keys);
+        });
+    }
+
+    let call = match sig.asyncness {
+        Some(_) => quote!(Self::#name(#(#args),*).await),
+        None => quote!(Self::#name(#(#args),*)),
+    };
+    let req = match types.is_empty() {
+        true => quote!(_req),
+        false => quote!(mut req),
+    };
+
+    Ok(quote! {
+        #(#docs)*
+        #[allow(non_camel_case_types)]
+        #[derive(Clone, Copy, Debug)]
+        #vis struct #name;
+
+        impl #name {
+            #(#attrs)*
+            #vis #sig #body
+        }
+
+        #[#this::async_trait(?Send)]
+        impl #this::Endpoint for #name {
+            async fn call(&self, #req: #this::Request) -> #this::Response {
+                #(#extract)*
+                #this::IntoResponse::into_response(#call)
+            }
+
+            fn name(&self) -> &str {
+                stringify!(#name)
+            }
+
+            fn required_state(&self) -> ::std::vec::Vec<#this::request::StateKey> {
+                #[allow(unused_mut)]
+                let mut keys = ::std::vec::Vec::new();
+                #(#required_state)*
+                keys
+            }
+        }
+    })
+}
+
+/// The types of the arguments of `sig`, rejecting those that can't be extractors.
+fn extractor_types(sig: &syn::Signature) -> syn::Result<Vec<&Type>> {
+    sig.inputs
+        .iter()
+        .map(|input| match parse_input_type(input) {
+            InputType::Extractor(p) => Ok(&*p.ty),
+            InputType::Reference(p) => Err(syn::Error::new_spanned(
+                &p.ty,
+                "handler arguments are extracted from the request by value, \
+                 take an extractor such as `Request`, `Path<T>` or `Json<T>` instead of a reference",
+            )),
+            InputType::ImplTrait(p) => Err(syn::Error::new_spanned(
+                &p.ty,
+                "`impl Trait` arguments are not supported by #[handler], name the extractor type",
+            )),
+            InputType::Receiver(r) => Err(syn::Error::new_spanned(
+                r,
+                "#[handler] functions cannot take `self`",
+            )),
+        })
+        .collect()
+}
@@ -0,0 +1,129 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Attribute, Data, DeriveInput, LitInt, LitStr, Token};
+
+use crate::ident_crate;
+
+/// The response attributes of the type, or of one of its variants.
+#[derive(Clone, Default)]
+struct Attrs {
+    status: Option<u16>,
+    json: bool,
+    headers: Vec<(LitStr, LitStr)>,
+}
+
+impl Attrs {
+    fn parse(attrs: &[Attribute], mut parsed: Attrs) -> syn::Result<Attrs> {
+        let mut status = None;
+        for attr in attrs {
+            if attr.path().is_ident("status") {
+                if status.is_some() {
+                    return Err(syn::Error::new_spanned(attr, "duplicate `#[status]`"));
+                }
+                let code: LitInt = attr.parse_args()?;
+                let value = code.base10_parse::<u16>()?;
+                if !(100..=999).contains(&value) {
+                    return Err(syn::Error::new_spanned(
+                        code,
+                        "status codes range from 100 to 999",
+                    ));
+                }
+                status = Some(value);
+            } else if attr.path().is_ident("json") {
+                attr.meta.require_path_only()?;
+                parsed.json = true;
+            } else if attr.path().is_ident("header") {
+                let (name, value) = attr.parse_args_with(|input: syn::parse::ParseStream| {
+                    let name: LitStr = input.parse()?;
+                    input.parse::<Token![,]>()?;
+                    let value: LitStr = input.parse()?;
+                    Ok((name, value))
+                })?;
+                if http_header_name_is_invalid(&name.value()) {
+                    return Err(syn::Error::new_spanned(
+                        name,
+                        "header names must be lowercase ASCII tokens",
+                    ));
+                }
+                if !value
+                    .value()
+                    .bytes()
+                    .all(|b| b == b'\t' || (32..127).contains(&b))
+                {
+                    return Err(syn::Error::new_spanned(
+                        value,
+                        "header values must be visible ASCII characters",
+                    ));
+                }
+                parsed.headers.push((name, value));
+            }
+        }
+        parsed.status = status.or(parsed.status);
+        Ok(parsed)
+    }
+}
+
+fn http_header_name_is_invalid(name: &str) -> bool {
+    name.is_empty()
+        || !name.bytes().all(|b| {
+            b.is_ascii_lowercase() || b.is_ascii_digit() || b"!#$%&'*+-.^_`|~".contains(&b)
+        })
+}
+
+pub(crate) fn generate(input: DeriveInput) -> syn::Result<TokenStream> {
+    let this = ident_crate();
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let outer = Attrs::parse(&input.attrs, Attrs::default())?;
+
+    let arms = match &input.data {
+        Data::Enum(data) => data
+            .variants
+            .iter()
+            .map(|variant| {
+                let attrs = Attrs::parse(&variant.attrs, outer.clone())?;
+                let ident = &variant.ident;
+                let response = response_parts(&attrs);
+                Ok(quote!(Self::#ident { .. } => #response,))
+            })
+            .collect::<syn::Result<Vec<_>>>()?,
+        Data::Struct(_) => {
+            let response = response_parts(&outer);
+            vec![quote!(_ => #response,)]
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                name,
+                "#[derive(IntoResponse)] does not support unions",
+            ));
+        }
+    };
+
+    Ok(quote! {
+        impl #impl_generics #this::IntoResponse for #name #ty_generics #where_clause {
+            fn into_response(self) -> #this::Response {
+                let (status, json, headers): (u16, bool, &[(&'static str, &'static str)]) =
+                    match &self {
+                        #(#arms)*
+                    };
+                #this::response::__private::error_response(
+                    #this::http::StatusCode::from_u16(status)
+                        .expect("status codes are checked by #[derive(IntoResponse)]"),
+                    ::std::string::ToString::to_string(&self),
+                    json,
+                    headers,
+                )
+            }
+        }
+    })
+}
+
+fn response_parts(attrs: &Attrs) -> TokenStream {
+    let status = attrs.status.unwrap_or(500);
+    let json = attrs.json;
+    let headers = attrs
+        .headers
+        .iter()
+        .map(|(name, value)| quote!((#name, #value)));
+    quote!((#status, #json, &[#(#headers),*]))
+}
@@ -1,152 +1,156 @@
-#![allow(clippy::all)]
-#![allow(warnings)]
-use proc_macro::{Span, TokenStream};
+use proc_macro::TokenStream;
 use proc_macro_crate::{FoundCrate, crate_name};
-use proc_macro2::{Span as Span2, TokenStream as TokenStream2};
-use quote::{ToTokens, quote};
-use syn::{DeriveInput, Ident, Item, ReturnType, Signature, parse_macro_input};
-
-use crate::util::{InputType, parse_input_type};
+use proc_macro2::Span;
+use syn::{Ident, Item, parse_macro_input};
 
+mod from_request;
 mod handler;
+mod into_response;
+mod route;
 mod util;
 
+/// Turn an async fn taking extractor arguments into a unit struct of the same name that
+/// implements `Endpoint`, with `name()` returning the function name.
+///
+/// ```ignore
+/// #[handler]
+/// async fn show_user(Path(user): Path<UserPath>, AppState(db): AppState<Db>) -> String {
+///     db.name_of(user.id)
+/// }
+///
+/// let app = Router::new().at("/user/{id}", get(show_user));
+/// ```
 #[proc_macro_attribute]
 pub fn handler(_args: TokenStream, input: TokenStream) -> TokenStream {
-    let item = parse_macro_input!(input);
-    match generate(item) {
+    let item = parse_macro_input!(input as Item);
+    let result = match item {
+        Item::Fn(item_fn) => handler::generate(item_fn),
+        _ => Err(syn::Error::new_spanned(
+            item,
+            "#[handler] can only be added to a `fn`",
+        )),
+    };
+    match result {
         Ok(stream) => stream.into(),
         Err(e) => e.to_compile_error().into(),
     }
 }
 
-pub(crate) fn generate(input: Item) -> syn::Result<TokenStream2> {
-    let this = ident_crate();
-
-    match input {
-        Item::Fn(mut item_fn) => {
-            let attrs = item_fn
-                .attrs
-                .iter()
-                .filter(|a| !a.path().is_ident("handler"))
-                .collect::<Vec<_>>();
-            let vis = &item_fn.vis;
-            let sig = &mut item_fn.sig;
-            let body = &item_fn.block;
-            let name = &sig.ident;
-            let docs = item_fn
-                .attrs
-                .iter()
-                .filter(|a| !a.path().is_ident("doc"))
-                .collect::<Vec<_>>();
-            let struct_impl = quote! {
-                #(#docs)*
-                #[allow(non_camel_case_types)]
-                #[derive(Debug)]
-                #vis struct #name;
-                impl #name {
-                    #(#attrs)*
-                    #sig {
-                        #body
-                    }
-                }
-            };
-
-            let handler_impl = gen_handler_impl(&this, sig)?;
-
-            Ok(quote! {
-                #struct_impl
-                #[#this::async_trait(?Send)]
-                impl #this::Handler for #name {
-                    #handler_impl
-                }
-
-            })
-        }
-        _ => Err(syn::Error::new_spanned(
-            input,
-            "#[handler] must added to `impl` or `fn`",
-        )),
+/// Derive an extractor for a struct whose fields come from different parts of the request.
+///
+/// Every field is annotated with its source:
+///
+/// - `#[from(path)]`: deserialized like `Path<T>`.
+/// - `#[from(query)]`: deserialized like `Query<T>`.
+/// - `#[from(header = "x-tenant")]`: parsed with `FromStr`. A missing header is an error,
+///   unless the field is an `Option`.
+/// - `#[from(state)]`: an `AppState<T>` sharing the application state, without cloning it.
+/// - `#[from(json)]`: deserialized like `Json<T>`. At most one field can read the body, and
+///   the struct then only implements `FromRequest`, so it must be the last handler argument.
+///
+/// All the fields are extracted even when one fails, so the `FieldErrors` rejection lists every
+/// problem with the request at once.
+///
+/// ```ignore
+/// #[derive(FromRequest)]
+/// struct CreateOrder {
+///     #[from(path)]
+///     shop: ShopPath,
+///     #[from(header = "x-tenant")]
+///     tenant: String,
+///     #[from(json)]
+///     order: NewOrder,
+/// }
+/// ```
+#[proc_macro_derive(FromRequest, attributes(from))]
+pub fn derive_from_request(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as syn::DeriveInput);
+    match from_request::generate(input) {
+        Ok(stream) => stream.into(),
+        Err(e) => e.to_compile_error().into(),
     }
 }
 
-fn gen_handler_impl(this: &Ident, sig: &Signature) -> syn::Result<TokenStream2> {
-    let name = &sig.ident;
-    let mut call_args: Vec<Ident> = Vec::with_capacity(sig.inputs.len());
-
-    for input in &sig.inputs {
-        match parse_input_type(input) {
-            InputType::Receiver(_) => call_args.push(Ident::new("self", Span2::call_site())),
-            InputType::Request(_) => call_args.push(Ident::new("req", Span2::call_site())),
-            InputType::Response(_) => call_args.push(Ident::new("resp", Span2::call_site())),
-            InputType::Unknown => {
-                let msg = "the inputs parameters must be Request, Response";
-                return Err(syn::Error::new_spanned(&sig.inputs, msg));
-            }
-            _ => {
-                let msg = "the inputs parameters must be Request, Response";
-                return Err(syn::Error::new_spanned(&sig.inputs, msg));
-            }
-        }
+/// Implement `IntoResponse` for an error type, rendering its `Display` message with a status
+/// code picked per variant.
+///
+/// - `#[status(404)]`: the status of the variant, or of every variant when put on the type.
+///   Defaults to `500 Internal Server Error`.
+/// - `#[json]`: render the message as `{"error": "..."}` instead of plain text.
+/// - `#[header("retry-after", "30")]`: add a header, can be repeated.
+///
+/// ```ignore
+/// #[derive(Debug, thiserror::Error, IntoResponse)]
+/// #[json]
+/// enum ShopError {
+///     #[error("order {0} not found")]
+///     #[status(404)]
+///     OrderNotFound(u32),
+///     #[error("the shop is closed")]
+///     #[status(503)]
+///     #[header("retry-after", "3600")]
+///     Closed,
+/// }
+/// ```
+#[proc_macro_derive(IntoResponse, attributes(status, json, header))]
+pub fn derive_into_response(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as syn::DeriveInput);
+    match into_response::generate(input) {
+        Ok(stream) => stream.into(),
+        Err(e) => e.to_compile_error().into(),
     }
+}
 
-    match sig.output {
-        ReturnType::Default => {
-            if sig.asyncness.is_none() {
-                Ok(quote! {
-                    async fn handle(
-                        &self,
-                        req: &mut #this::Request,
-                        resp: &mut #this::Response,
-                    ) {
-                        Self::#name(#(#call_args), *)
-                    }
-                })
-            } else {
-                Ok(quote! {
-                    async fn handle(
-                        &self,
-                        req: &mut #this::Request,
-                        resp: &mut #this::Response,
-                    ) {
-                        Self::#name(#(#call_args), *).await
-                    }
-                })
-            }
-        }
-        ReturnType::Type(..) => {
-            if sig.asyncness.is_none() {
-                Ok(quote! {
-                    async fn handle(
-                        &self,
-                        req: &mut #this::Request,
-                        resp: &mut #this::Response,
-                    ) {
-                        #this::Writer::write(Self::#name(#(#call_args),*), req, resp).await;
-                    }
-                })
-            } else {
-                Ok(quote! {
-                    async fn handle(
-                        &self,
-                        req: &mut #this::Request,
-                        resp: &mut #this::Response,
-                    ) {
-                        Self::#name(#(#call_args), *).await
-                        #this::Writer::write(Self::#name(#(#call_args),*).await, req, resp).await;
-                    }
-                })
+macro_rules! route_attributes {
+    ($($(#[$doc:meta])* $name:ident => $method:ident,)*) => {
+        $(
+            $(#[$doc])*
+            #[proc_macro_attribute]
+            pub fn $name(args: TokenStream, input: TokenStream) -> TokenStream {
+                let path = parse_macro_input!(args as syn::LitStr);
+                let item = parse_macro_input!(input as syn::ItemFn);
+                match route::generate(path, stringify!($method), item) {
+                    Ok(stream) => stream.into(),
+                    Err(e) => e.to_compile_error().into(),
+                }
             }
-        }
-    }
+        )*
+    };
+}
+
+route_attributes! {
+    /// Like [`macro@handler`], and make the handler serve `GET` requests at the given path when
+    /// passed to `Router::mount` or `routes!`.
+    ///
+    /// ```ignore
+    /// #[get("/users/{id}")]
+    /// async fn show_user(Path(user): Path<UserPath>) -> String {
+    ///     user.id.to_string()
+    /// }
+    ///
+    /// let app = Router::new().mount(show_user);
+    /// ```
+    get => GET,
+    /// Like [`macro@get`], for `POST` requests.
+    post => POST,
+    /// Like [`macro@get`], for `PUT` requests.
+    put => PUT,
+    /// Like [`macro@get`], for `DELETE` requests.
+    delete => DELETE,
+    /// Like [`macro@get`], for `PATCH` requests.
+    patch => PATCH,
+    /// Like [`macro@get`], for `HEAD` requests.
+    head => HEAD,
+    /// Like [`macro@get`], for `OPTIONS` requests.
+    options => OPTIONS,
 }
 
 pub(crate) fn ident_crate() -> Ident {
     match crate_name("monet") {
         Ok(monet) => match monet {
-            FoundCrate::Itself => Ident::new("monet", Span2::call_site()),
-            FoundCrate::Name(name) => Ident::new(&name, Span2::call_site()),
+            FoundCrate::Itself => Ident::new("monet", Span::call_site()),
+            FoundCrate::Name(name) => Ident::new(&name, Span::call_site()),
         },
-        Err(_) => Ident::new("monet", Span2::call_site()),
+        Err(_) => Ident::new("monet", Span::call_site()),
     }
 }
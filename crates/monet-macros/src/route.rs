@@ -0,0 +1,28 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{ItemFn, LitStr};
+
+use crate::{handler, ident_crate};
+
+pub(crate) fn generate(path: LitStr, method: &str, item_fn: ItemFn) -> syn::Result<TokenStream> {
+    if !path.value().starts_with('/') {
+        return Err(syn::Error::new_spanned(
+            &path,
+            "route paths must start with `/`",
+        ));
+    }
+
+    let this = ident_crate();
+    let name = item_fn.sig.ident.clone();
+    let method = format_ident!("{}", method);
+    let handler = handler::generate(item_fn)?;
+
+    Ok(quote! {
+        #handler
+
+        impl #this::router::RouteEndpoint for #name {
+            const PATH: &'static str = #path;
+            const METHOD: #this::http::Method = #this::http::Method::#method;
+        }
+    })
+}
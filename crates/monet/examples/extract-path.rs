@@ -1,6 +1,6 @@
 use std::net::SocketAddr;
 
-use monet::{Error, Path, Request, Router, get};
+use monet::{Path, Router, get};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize)]
@@ -9,9 +9,8 @@ struct Expected {
     name: String,
 }
 
-async fn parse_path(req: Request) -> Result<String, Error> {
-    let path: Path<Expected> = req.path()?;
-    Ok(format!("Received id: {}, name: {}", path.id, path.name))
+async fn parse_path(Path(path): Path<Expected>) -> String {
+    format!("Received id: {}, name: {}", path.id, path.name)
 }
 
 // curl http://0.0.0.0:9527/wild/8797/card/larry.
@@ -20,7 +19,7 @@ fn main() {
     let addr: SocketAddr = ([0, 0, 0, 0], 9527).into();
     println!("Server running at: {}", addr);
 
-    let app = Router::new().at("/wild/{id}/card/{*name}", get(parse_path));
+    let app = Router::new().at("/wild/{id:int}/card/{*name}", get(parse_path));
 
     monet::run(addr, app);
 }
@@ -6,8 +6,8 @@ use std::{
 
 use http::header::HeaderValue;
 use monet::{
-    Form, Json, Layer, Middleware, Response, Router, async_trait, error::Error, get,
-    handler::endpoint::serve_dir::ServeDir, post, request::Request, types::Html,
+    Form, Json, Layer, Middleware, Query, Response, Router, async_trait, error::Error, get,
+    handler, handler::endpoint::serve_dir::ServeDir, post, request::Request, types::Html,
 };
 use serde::{Deserialize, Serialize};
 
@@ -30,14 +30,6 @@ pub struct SyncedState(i32);
 static NUM: LazyLock<Arc<Mutex<SyncedState>>> =
     LazyLock::new(|| Arc::new(Mutex::new(SyncedState(42))));
 
-async fn set_state(mut req: Request, layer: Layer) -> Response {
-    let s = &*NUM;
-    req.state.insert(s.clone());
-    req.state.insert::<SyncedState>(SyncedState(99));
-
-    layer.next(req).await
-}
-
 #[derive(Deserialize)]
 pub struct Pagination {
     pub page: i32,
@@ -47,16 +39,15 @@ pub struct Pagination {
 async fn root(req: Request) -> String {
     compio::runtime::time::sleep(std::time::Duration::from_millis(1000)).await;
 
-    // let guard = _req.state::<Arc<Mutex<SyncedState>>>().unwrap();
-    let guard: &Arc<Mutex<SyncedState>> = req.state.get().unwrap();
+    let guard = req.app_state::<Arc<Mutex<SyncedState>>>().unwrap();
     let mut i = guard.lock().unwrap();
     i.0 += 1;
     format!("Hi count is {}", i.0)
 }
 
-async fn query(req: Request) -> Result<String, Error> {
-    let q = req.query::<Pagination>()?;
-    Ok(q.offset.to_string())
+#[handler]
+async fn query(Query(q): Query<Pagination>) -> String {
+    q.offset.to_string()
 }
 
 async fn parse_json(req: Request) -> Result<Json<UserPayload>, Error> {
@@ -120,8 +111,8 @@ fn main() {
         .at("/form", post(parse_form))
         .at("/html", get(return_html))
         .wrap_by(RequestCounter)
-        .wrap_by(set_state)
-        .at("/hello.html", get(service));
+        .at("/hello.html", get(service))
+        .with_state(NUM.clone());
 
     monet::run(addr, app);
 }
@@ -1,6 +1,6 @@
 use std::net::SocketAddr;
 
-use monet::{Error, Json, Request, Router, get};
+use monet::{Json, Router, get};
 use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
 
@@ -10,12 +10,12 @@ pub struct UserPayload {
     pub password: String,
 }
 
-async fn parse_json(req: Request) -> Result<Json<UserPayload>, Error> {
-    req.into_json().await
+async fn parse_json(Json(user): Json<UserPayload>) -> Json<UserPayload> {
+    Json(user)
 }
 
 // Return a serde_json::Value using the json! macro
-async fn return_json(_req: Request) -> Json<Value> {
+async fn return_json() -> Json<Value> {
     let data = json!({
         "status": "success",
         "data": { "id": 1, "name": "example" }
@@ -0,0 +1,63 @@
+use std::net::SocketAddr;
+
+use http::StatusCode;
+use monet::{
+    Json, Path, Router, get,
+    openapi::{DocumentedHandler, Info, Operation},
+    post,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, JsonSchema)]
+struct UserId {
+    id: u32,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema)]
+struct User {
+    id: u32,
+    name: String,
+}
+
+async fn show_user(Path(UserId { id }): Path<UserId>) -> Json<User> {
+    Json(User {
+        id,
+        name: "larry".to_string(),
+    })
+}
+
+async fn create_user(user: Json<User>) -> Json<User> {
+    user
+}
+
+// curl http://0.0.0.0:9527/openapi.json
+fn main() {
+    let addr: SocketAddr = ([0, 0, 0, 0], 9527).into();
+    println!("Server running at: {}", addr);
+
+    let app = Router::new()
+        .at(
+            "/users/{id:uint}",
+            get(show_user.doc(
+                Operation::new()
+                    .summary("Fetch a user")
+                    .tag("users")
+                    .response::<Json<User>>(StatusCode::OK, "The user"),
+            )),
+        )
+        .at(
+            "/users",
+            post(
+                create_user.doc(
+                    Operation::new()
+                        .summary("Create a user")
+                        .tag("users")
+                        .response::<Json<User>>(StatusCode::OK, "The created user"),
+                ),
+            ),
+        )
+        .serve_openapi("/openapi.json", Info::new("Users", "1.0.0"));
+
+    monet::run(addr, app);
+}
@@ -0,0 +1,50 @@
+use std::{cell::RefCell, collections::HashMap, net::SocketAddr, rc::Rc};
+
+use monet::{Error, Mesh, Request, Router, Server, get, post};
+
+/// A cache owned by a single core, so a `RefCell` is all the synchronisation it needs.
+struct Cache {
+    worker: usize,
+    hits: RefCell<HashMap<String, u32>>,
+}
+
+async fn hit(req: Request) -> String {
+    let cache = req.app_state::<Cache>().unwrap();
+    let mut hits = cache.hits.borrow_mut();
+    let count = hits.entry(req.uri().path().to_string()).or_default();
+    *count += 1;
+    format!("worker {} served {} {count} times", cache.worker, req.uri())
+}
+
+/// Sent to every core to forget the hits they counted.
+#[derive(Clone)]
+struct Reset;
+
+async fn reset(req: Request) -> Result<&'static str, Error> {
+    let mesh = req.app_state::<Mesh>()?;
+    mesh.broadcast(Reset).expect("all workers are running");
+    Ok("reset on every worker")
+}
+
+// curl http://0.0.0.0:9527/anything
+// curl -X POST http://0.0.0.0:9527/reset
+fn main() {
+    let addr: SocketAddr = ([0, 0, 0, 0], 9527).into();
+    println!("Server running at: {}", addr);
+
+    Server::bind(addr)
+        .workers(4)
+        .on_worker_start(async |worker| Cache {
+            worker,
+            hits: Default::default(),
+        })
+        .on_worker_stop(async |cache: Rc<Cache>| {
+            println!("worker {} stopped", cache.worker);
+        })
+        .on_message(async |Reset, cache: Rc<Cache>| cache.hits.borrow_mut().clear())
+        .serve(|| {
+            Router::new()
+                .at("/reset", post(reset))
+                .at("/{*path}", get(hit))
+        });
+}
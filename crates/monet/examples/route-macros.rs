@@ -0,0 +1,39 @@
+use std::net::SocketAddr;
+
+use monet::{Json, Path, Router, get, post, routes};
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize)]
+struct User {
+    id: u32,
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct UserPath {
+    id: u32,
+}
+
+#[get("/users/{id:uint}")]
+async fn show_user(Path(user): Path<UserPath>) -> Json<User> {
+    Json(User {
+        id: user.id,
+        name: format!("user {}", user.id),
+    })
+}
+
+#[post("/users")]
+async fn create_user(Json(user): Json<User>) -> Json<User> {
+    Json(user)
+}
+
+// curl http://0.0.0.0:9527/api/users/7
+// Expect: {"id":7,"name":"user 7"}
+fn main() {
+    let addr: SocketAddr = ([0, 0, 0, 0], 9527).into();
+    println!("Server running at: {}", addr);
+
+    let app = Router::new().nest("/api", routes![show_user, create_user]);
+
+    monet::run(addr, app);
+}
@@ -1,12 +1,18 @@
+mod stream;
+
 use std::{
     pin::Pin,
     task::{Context, Poll},
 };
 
 use bytes::Bytes;
+use compio::{BufResult, io::AsyncRead};
+use futures::{Stream, TryStreamExt};
 use http_body::Frame;
 use http_body_util::BodyExt;
+use send_wrapper::SendWrapper;
 
+pub use self::stream::{BodyReader, BodyStream};
 use crate::error::{BodyError, BoxError};
 
 type BoxBody = http_body_util::combinators::UnsyncBoxBody<Bytes, BodyError>;
@@ -29,6 +35,61 @@ impl Body {
     pub fn empty() -> Self {
         Self::new(http_body_util::Empty::new())
     }
+
+    /// Create a body sending the chunks of `stream` as they are produced.
+    ///
+    /// An error ends the body abruptly, the client sees a truncated response.
+    pub fn from_stream<S, D, E>(stream: S) -> Self
+    where
+        S: Stream<Item = Result<D, E>> + 'static,
+        D: Into<Bytes>,
+        E: Into<BoxError> + 'static,
+    {
+        Self::from_frames(stream.map_ok(|data| Frame::data(data.into())))
+    }
+
+    /// Create a body sending everything read from a compio reader, e.g. a file or an upstream
+    /// connection, in chunks of up to 16 KiB.
+    pub fn from_reader<R: AsyncRead + 'static>(reader: R) -> Self {
+        let chunks = futures::stream::try_unfold(reader, async |mut reader| {
+            let BufResult(read, chunk) = reader.read(Vec::with_capacity(READ_CHUNK)).await;
+            Ok::<_, std::io::Error>(match read? {
+                0 => None,
+                _ => Some((Bytes::from(chunk), reader)),
+            })
+        });
+        Self::from_stream(chunks)
+    }
+
+    /// Create a body from a stream of frames that need not be `Send`.
+    pub(crate) fn from_frames<S, E>(frames: S) -> Self
+    where
+        S: Stream<Item = Result<Frame<Bytes>, E>> + 'static,
+        E: Into<BoxError> + 'static,
+    {
+        let frames: LocalFrames = Box::pin(frames.map_err(Into::into));
+        Self::new(LocalBody(SendWrapper::new(frames)))
+    }
+}
+
+const READ_CHUNK: usize = 16 * 1024;
+
+type LocalFrames = Pin<Box<dyn Stream<Item = Result<Frame<Bytes>, BoxError>>>>;
+
+/// A body that stays on the worker that created it, like everything else handling a
+/// request.
+struct LocalBody(SendWrapper<LocalFrames>);
+
+impl http_body::Body for LocalBody {
+    type Data = Bytes;
+    type Error = BoxError;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        self.0.as_mut().poll_next(cx)
+    }
 }
 
 fn boxed<B>(body: B) -> BoxBody
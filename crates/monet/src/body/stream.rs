@@ -0,0 +1,137 @@
+use std::{
+    io,
+    pin::Pin,
+    task::{Context, Poll, ready},
+};
+
+use bytes::{Buf, Bytes};
+use compio::{BufResult, buf::IoBufMut, io::AsyncRead};
+use futures::{Stream, StreamExt};
+use http::HeaderMap;
+use http_body::Body as _;
+
+use crate::{body::Body, error::Error};
+
+/// The data of a request body as a [`Stream`] of [`Bytes`] chunks, read as they arrive.
+///
+/// The trailers sent after the data, if any, are available from [`BodyStream::trailers`]
+/// once the stream is over.
+#[derive(Debug)]
+pub struct BodyStream {
+    body: Result<Body, Option<Error>>,
+    limit: usize,
+    trailers: Option<HeaderMap>,
+    done: bool,
+}
+
+impl BodyStream {
+    /// Stream `body`, limited to `limit` bytes if it went through
+    /// [`Request::into_limited_body`](crate::Request::into_limited_body).
+    pub(crate) fn new(body: Result<Body, Error>, limit: Option<usize>) -> Self {
+        Self {
+            body: body.map_err(Some),
+            limit: limit.unwrap_or(usize::MAX),
+            trailers: None,
+            done: false,
+        }
+    }
+
+    /// The trailers of the body, once the stream has returned `None`.
+    pub fn trailers(&self) -> Option<&HeaderMap> {
+        self.trailers.as_ref()
+    }
+
+    /// Read the data through the compio [`AsyncRead`] trait instead, e.g. to copy it into a
+    /// file.
+    pub fn into_reader(self) -> BodyReader {
+        BodyReader {
+            stream: self,
+            chunk: Bytes::new(),
+        }
+    }
+}
+
+impl Stream for BodyStream {
+    type Item = Result<Bytes, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        if this.done {
+            return Poll::Ready(None);
+        }
+        let body = match &mut this.body {
+            Ok(body) => body,
+            Err(err) => {
+                this.done = true;
+                return Poll::Ready(err.take().map(Err));
+            }
+        };
+        loop {
+            match ready!(Pin::new(&mut *body).poll_frame(cx)) {
+                Some(Ok(frame)) => match frame.into_data() {
+                    Ok(data) if data.is_empty() => continue,
+                    Ok(data) => return Poll::Ready(Some(Ok(data))),
+                    Err(frame) => {
+                        if let Ok(trailers) = frame.into_trailers() {
+                            match &mut this.trailers {
+                                Some(existing) => existing.extend(trailers),
+                                None => this.trailers = Some(trailers),
+                            }
+                        }
+                    }
+                },
+                Some(Err(err)) => {
+                    this.done = true;
+                    return Poll::Ready(Some(Err(match err.is_length_limit() {
+                        true => Error::PayloadTooLarge { limit: this.limit },
+                        false => err.into(),
+                    })));
+                }
+                None => {
+                    this.done = true;
+                    return Poll::Ready(None);
+                }
+            }
+        }
+    }
+}
+
+/// The data of a request body read through the compio [`AsyncRead`] trait.
+///
+/// ```ignore
+/// async fn backup(req: Request) -> std::io::Result<String> {
+///     let mut upstream = compio::net::TcpStream::connect("backup:9000").await?;
+///     let copied = compio::io::copy(&mut req.into_reader(), &mut upstream).await?;
+///     Ok(format!("{copied} bytes"))
+/// }
+/// ```
+#[derive(Debug)]
+pub struct BodyReader {
+    stream: BodyStream,
+    chunk: Bytes,
+}
+
+impl BodyReader {
+    /// The trailers of the body, once a read has returned 0.
+    pub fn trailers(&self) -> Option<&HeaderMap> {
+        self.stream.trailers()
+    }
+}
+
+impl AsyncRead for BodyReader {
+    async fn read<B: IoBufMut>(&mut self, buf: B) -> BufResult<usize, B> {
+        while self.chunk.is_empty() {
+            match self.stream.next().await {
+                Some(Ok(chunk)) => self.chunk = chunk,
+                Some(Err(err)) => return BufResult(Err(io::Error::other(err)), buf),
+                None => return BufResult(Ok(0), buf),
+            }
+        }
+        let mut chunk = &self.chunk[..];
+        let BufResult(read, buf) = chunk.read(buf).await;
+        if let Ok(n) = read {
+            self.chunk.advance(n);
+        }
+        BufResult(read, buf)
+    }
+}
@@ -0,0 +1,282 @@
+//! Reading cookies from requests and setting them in responses.
+//!
+//! A [`CookieJar`] extracted from a request holds the cookies the client sent. Adding or
+//! removing cookies returns the modified jar, which is then returned from the handler, alone or
+//! as the first element of a `(jar, response)` tuple, to emit the matching `Set-Cookie` headers:
+//!
+//! ```ignore
+//! async fn login(jar: CookieJar) -> (CookieJar, &'static str) {
+//!     let session = Cookie::build(("session", "abc"))
+//!         .path("/")
+//!         .http_only(true)
+//!         .secure(true)
+//!         .same_site(SameSite::Lax)
+//!         .max_age(Duration::days(7));
+//!     (jar.add(session), "welcome")
+//! }
+//! ```
+//!
+//! With the `cookie-signed` and `cookie-private` features, [`SignedCookieJar`] and
+//! [`PrivateCookieJar`] authenticate, respectively encrypt, their cookies with the [`Key`]
+//! provided as application state with [`Router::with_state`]. Cookies that fail verification
+//! are ignored as if the client never sent them.
+//!
+//! [`Router::with_state`]: crate::Router::with_state
+
+#[cfg(test)]
+mod tests;
+
+use std::convert::Infallible;
+#[cfg(any(feature = "cookie-signed", feature = "cookie-private"))]
+use std::rc::Rc;
+
+use async_trait::async_trait;
+#[cfg(any(feature = "cookie-signed", feature = "cookie-private"))]
+pub use cookie::Key;
+pub use cookie::{Cookie, Expiration, SameSite, time::Duration};
+use http::{HeaderMap, HeaderValue, header};
+
+#[cfg(any(feature = "cookie-signed", feature = "cookie-private"))]
+use crate::{error::Error, request::StateKey};
+use crate::{
+    extract::FromRequestParts,
+    request::Request,
+    response::{IntoResponse, Response},
+};
+
+/// The cookies of a request, and the changes to send back with the response.
+#[derive(Clone, Debug, Default)]
+pub struct CookieJar {
+    jar: cookie::CookieJar,
+}
+
+impl CookieJar {
+    /// Read the cookies of every `Cookie` header. Malformed cookies are skipped.
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let mut jar = cookie::CookieJar::new();
+        parse_cookies(headers).for_each(|cookie| jar.add_original(cookie));
+        CookieJar { jar }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Cookie<'static>> {
+        self.jar.get(name)
+    }
+
+    /// Add a cookie, replacing the one of the same name.
+    #[must_use]
+    #[allow(clippy::should_implement_trait)]
+    pub fn add(mut self, cookie: impl Into<Cookie<'static>>) -> Self {
+        self.jar.add(cookie);
+        self
+    }
+
+    /// Remove a cookie, telling the client to delete it. The path and domain must be the same
+    /// as when it was added.
+    #[must_use]
+    pub fn remove(mut self, cookie: impl Into<Cookie<'static>>) -> Self {
+        self.jar.remove(cookie);
+        self
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Cookie<'static>> {
+        self.jar.iter()
+    }
+}
+
+#[async_trait(?Send)]
+impl FromRequestParts for CookieJar {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(req: &mut Request) -> Result<Self, Self::Rejection> {
+        Ok(Self::from_headers(req.headers()))
+    }
+}
+
+/// A [`CookieJar`] whose cookies are signed, so the client can read but not tamper with them.
+#[cfg(feature = "cookie-signed")]
+#[derive(Clone)]
+pub struct SignedCookieJar {
+    jar: cookie::CookieJar,
+    key: Rc<Key>,
+}
+
+#[cfg(feature = "cookie-signed")]
+impl SignedCookieJar {
+    /// Read the cookies of every `Cookie` header, keeping those with a valid signature.
+    pub fn from_headers(headers: &HeaderMap, key: Rc<Key>) -> Self {
+        let mut jar = cookie::CookieJar::new();
+        for cookie in parse_cookies(headers) {
+            if jar.signed(&key).verify(cookie.clone()).is_some() {
+                jar.add_original(cookie);
+            }
+        }
+        SignedCookieJar { jar, key }
+    }
+
+    /// The cookie named `name`, with its signature removed.
+    pub fn get(&self, name: &str) -> Option<Cookie<'static>> {
+        self.jar.signed(&self.key).get(name)
+    }
+
+    /// Sign and add a cookie, replacing the one of the same name.
+    #[must_use]
+    #[allow(clippy::should_implement_trait)]
+    pub fn add(mut self, cookie: impl Into<Cookie<'static>>) -> Self {
+        self.jar.signed_mut(&self.key).add(cookie);
+        self
+    }
+
+    /// Remove a cookie, telling the client to delete it. The path and domain must be the same
+    /// as when it was added.
+    #[must_use]
+    pub fn remove(mut self, cookie: impl Into<Cookie<'static>>) -> Self {
+        self.jar.signed_mut(&self.key).remove(cookie);
+        self
+    }
+}
+
+#[cfg(feature = "cookie-signed")]
+impl std::fmt::Debug for SignedCookieJar {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SignedCookieJar")
+            .field("jar", &self.jar)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "cookie-signed")]
+#[async_trait(?Send)]
+impl FromRequestParts for SignedCookieJar {
+    type Rejection = Error;
+
+    async fn from_request_parts(req: &mut Request) -> Result<Self, Self::Rejection> {
+        let key = cookie_key(req)?;
+        Ok(Self::from_headers(req.headers(), key))
+    }
+
+    fn required_state(keys: &mut Vec<StateKey>) {
+        keys.push(StateKey::of::<Key>());
+    }
+}
+
+/// A [`CookieJar`] whose cookies are encrypted, so the client can neither read nor tamper with
+/// them.
+#[cfg(feature = "cookie-private")]
+#[derive(Clone)]
+pub struct PrivateCookieJar {
+    jar: cookie::CookieJar,
+    key: Rc<Key>,
+}
+
+#[cfg(feature = "cookie-private")]
+impl PrivateCookieJar {
+    /// Read the cookies of every `Cookie` header, keeping those that decrypt with the key.
+    pub fn from_headers(headers: &HeaderMap, key: Rc<Key>) -> Self {
+        let mut jar = cookie::CookieJar::new();
+        for cookie in parse_cookies(headers) {
+            if jar.private(&key).decrypt(cookie.clone()).is_some() {
+                jar.add_original(cookie);
+            }
+        }
+        PrivateCookieJar { jar, key }
+    }
+
+    /// The cookie named `name`, decrypted.
+    pub fn get(&self, name: &str) -> Option<Cookie<'static>> {
+        self.jar.private(&self.key).get(name)
+    }
+
+    /// Encrypt and add a cookie, replacing the one of the same name.
+    #[must_use]
+    #[allow(clippy::should_implement_trait)]
+    pub fn add(mut self, cookie: impl Into<Cookie<'static>>) -> Self {
+        self.jar.private_mut(&self.key).add(cookie);
+        self
+    }
+
+    /// Remove a cookie, telling the client to delete it. The path and domain must be the same
+    /// as when it was added.
+    #[must_use]
+    pub fn remove(mut self, cookie: impl Into<Cookie<'static>>) -> Self {
+        self.jar.private_mut(&self.key).remove(cookie);
+        self
+    }
+}
+
+#[cfg(feature = "cookie-private")]
+impl std::fmt::Debug for PrivateCookieJar {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PrivateCookieJar").finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "cookie-private")]
+#[async_trait(?Send)]
+impl FromRequestParts for PrivateCookieJar {
+    type Rejection = Error;
+
+    async fn from_request_parts(req: &mut Request) -> Result<Self, Self::Rejection> {
+        let key = cookie_key(req)?;
+        Ok(Self::from_headers(req.headers(), key))
+    }
+
+    fn required_state(keys: &mut Vec<StateKey>) {
+        keys.push(StateKey::of::<Key>());
+    }
+}
+
+macro_rules! impl_into_response {
+    ($($(#[$cfg:meta])* $jar:ty;)*) => {
+        $(
+            $(#[$cfg])*
+            impl IntoResponse for $jar {
+                fn into_response(self) -> Response {
+                    (self, ()).into_response()
+                }
+            }
+
+            $(#[$cfg])*
+            impl<R: IntoResponse> IntoResponse for ($jar, R) {
+                fn into_response(self) -> Response {
+                    let mut resp = self.1.into_response();
+                    set_cookies(&self.0.jar, resp.headers_mut());
+                    resp
+                }
+            }
+        )*
+    };
+}
+
+impl_into_response! {
+    CookieJar;
+    #[cfg(feature = "cookie-signed")]
+    SignedCookieJar;
+    #[cfg(feature = "cookie-private")]
+    PrivateCookieJar;
+}
+
+fn parse_cookies(headers: &HeaderMap) -> impl Iterator<Item = Cookie<'static>> + '_ {
+    headers
+        .get_all(header::COOKIE)
+        .into_iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(Cookie::split_parse_encoded)
+        .filter_map(Result::ok)
+        .map(Cookie::into_owned)
+}
+
+/// Add a `Set-Cookie` header for every cookie added or removed since the jar was extracted.
+fn set_cookies(jar: &cookie::CookieJar, headers: &mut HeaderMap) {
+    for cookie in jar.delta() {
+        if let Ok(value) = HeaderValue::from_str(&cookie.encoded().to_string()) {
+            headers.append(header::SET_COOKIE, value);
+        }
+    }
+}
+
+#[cfg(any(feature = "cookie-signed", feature = "cookie-private"))]
+fn cookie_key(req: &Request) -> Result<Rc<Key>, Error> {
+    req.app_state
+        .get_rc::<Key>()
+        .ok_or(Error::MissingAppState(std::any::type_name::<Key>()))
+}
@@ -0,0 +1,127 @@
+use http::{HeaderValue, Method, header};
+
+#[cfg(any(feature = "cookie-signed", feature = "cookie-private"))]
+use super::Key;
+#[cfg(feature = "cookie-private")]
+use super::PrivateCookieJar;
+#[cfg(feature = "cookie-signed")]
+use super::SignedCookieJar;
+use super::{Cookie, CookieJar, Duration, SameSite};
+use crate::{Router, get, router::tests::request};
+
+fn set_cookies(resp: &crate::Response) -> Vec<&str> {
+    resp.headers()
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .map(|value| value.to_str().unwrap())
+        .collect()
+}
+
+#[compio::test]
+async fn cookie_jar() {
+    async fn visit(jar: CookieJar) -> (CookieJar, String) {
+        let visits = jar
+            .get("visits")
+            .and_then(|c| c.value().parse::<u32>().ok())
+            .unwrap_or(0);
+        let cookie = Cookie::build(("visits", (visits + 1).to_string()))
+            .path("/")
+            .domain("example.com")
+            .max_age(Duration::hours(1))
+            .secure(true)
+            .http_only(true)
+            .same_site(SameSite::Strict);
+        let jar = jar.add(cookie).remove(Cookie::build("tracking").path("/"));
+        (jar, format!("visit {visits}"))
+    }
+
+    let app = Router::new().at("/", get(visit));
+    let mut req = request(Method::GET, "/");
+    req.headers_mut().insert(
+        header::COOKIE,
+        HeaderValue::from_static("visits=2; tracking=yes"),
+    );
+    let resp = app.handle(req).await;
+
+    let mut cookies = set_cookies(&resp);
+    cookies.sort();
+    assert_eq!(cookies.len(), 2);
+    assert!(cookies[0].starts_with("tracking=; Path=/; Max-Age=0"));
+    assert_eq!(
+        cookies[1],
+        "visits=3; HttpOnly; SameSite=Strict; Secure; Path=/; Domain=example.com; Max-Age=3600"
+    );
+}
+
+#[cfg(feature = "cookie-signed")]
+#[compio::test]
+async fn signed_cookie_jar() {
+    async fn whoami(jar: SignedCookieJar) -> (SignedCookieJar, String) {
+        let user = jar.get("user").map(|c| c.value().to_string());
+        (
+            jar.add(("user", "ferris")),
+            user.unwrap_or_else(|| "anonymous".into()),
+        )
+    }
+
+    let app = Router::new()
+        .at("/", get(whoami))
+        .with_state(Key::generate());
+
+    let resp = app.handle(request(Method::GET, "/")).await;
+    let signed = set_cookies(&resp)[0].to_string();
+    assert!(signed.starts_with("user=") && signed.ends_with("ferris"));
+
+    let body = async |cookie: &str| {
+        use http_body_util::BodyExt;
+
+        let mut req = request(Method::GET, "/");
+        req.headers_mut()
+            .insert(header::COOKIE, HeaderValue::from_str(cookie).unwrap());
+        let resp = app.handle(req).await;
+        let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    };
+    assert_eq!(body(&signed).await, "ferris");
+    assert_eq!(body(&signed.replace("ferris", "admin")).await, "anonymous");
+}
+
+#[cfg(feature = "cookie-private")]
+#[compio::test]
+async fn private_cookie_jar() {
+    async fn whoami(jar: PrivateCookieJar) -> (PrivateCookieJar, String) {
+        let user = jar.get("user").map(|c| c.value().to_string());
+        (
+            jar.add(("user", "ferris")),
+            user.unwrap_or_else(|| "anonymous".into()),
+        )
+    }
+
+    let app = Router::new()
+        .at("/", get(whoami))
+        .with_state(Key::generate());
+
+    let resp = app.handle(request(Method::GET, "/")).await;
+    let encrypted = set_cookies(&resp)[0].to_string();
+    let value = encrypted.strip_prefix("user=").unwrap();
+    assert!(!value.contains("ferris"));
+
+    let body = async |cookie: &str| {
+        use http_body_util::BodyExt;
+
+        let mut req = request(Method::GET, "/");
+        req.headers_mut()
+            .insert(header::COOKIE, HeaderValue::from_str(cookie).unwrap());
+        let resp = app.handle(req).await;
+        let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    };
+    assert_eq!(body(&encrypted).await, "ferris");
+
+    let mut tampered = value.as_bytes().to_vec();
+    let last = tampered.len() - 1;
+    tampered[last] = if tampered[last] == b'A' { b'B' } else { b'A' };
+    let tampered = format!("user={}", String::from_utf8(tampered).unwrap());
+    assert_eq!(body(&tampered).await, "anonymous");
+    assert_eq!(body("user=ferris").await, "anonymous");
+}
@@ -1,11 +1,20 @@
 use std::{error::Error as StdError, fmt};
 
 use http::StatusCode;
+use serde_json::Value;
 use thiserror::Error as ThisError;
 
-use crate::response::{IntoResponse, Response};
+use crate::{
+    problem::ProblemDetails,
+    response::{IntoResponse, Response},
+    router::error_hook::{CaughtFailure, Failure},
+};
 
+/// The errors of the extractors and request helpers.
+///
+/// Some variants only exist with the feature they belong to, so a match needs a wildcard arm.
 #[derive(ThisError, Debug)]
+#[non_exhaustive]
 pub enum Error {
     #[error("Failed to deserialize the JSON body into the target type: {0}")]
     JsonDataError(#[from] serde_path_to_error::Error<serde_json::Error>),
@@ -16,45 +25,290 @@ pub enum Error {
     #[error("Failed to buffer the request body: {0}")]
     UnknownBodyError(#[from] crate::BodyError),
 
+    #[error("Request body is larger than the limit of {limit} bytes")]
+    PayloadTooLarge { limit: usize },
+
+    #[error("Request body is not valid UTF-8")]
+    InvalidUtf8Body,
+
     #[error("Json request must have `Content-Type: application/json`")]
     InvalidJsonContentType,
 
     #[error("Form request must have `Content-Type: application/x-www-form-urlencoded`")]
     InvalidFormContentType,
 
+    #[cfg(feature = "msgpack")]
+    #[error("MessagePack request must have `Content-Type: application/msgpack`")]
+    InvalidMsgPackContentType,
+
+    #[cfg(feature = "msgpack")]
+    #[error("Failed to deserialize the MessagePack body: {0}")]
+    MsgPackError(#[source] serde_path_to_error::Error<rmp_serde::decode::Error>),
+
+    #[cfg(feature = "cbor")]
+    #[error("CBOR request must have `Content-Type: application/cbor`")]
+    InvalidCborContentType,
+
+    #[cfg(feature = "cbor")]
+    #[error("Failed to deserialize the CBOR body: {0}")]
+    CborError(#[source] ciborium::de::Error<std::io::Error>),
+
+    #[cfg(feature = "ndjson")]
+    #[error("NDJSON request must have `Content-Type: application/x-ndjson`")]
+    InvalidNdJsonContentType,
+
     #[error("Failed to deserialize Form: {0}")]
     FailedToDeserializeForm(#[source] serde_path_to_error::Error<serde_html_form::de::Error>),
 
     #[error("Failed to deserialize Query: {0}")]
-    FailedToDeserializeQuery(#[source] serde_path_to_error::Error<serde_urlencoded::de::Error>),
+    FailedToDeserializeQuery(#[source] serde_path_to_error::Error<serde_html_form::de::Error>),
+
+    #[cfg(feature = "nested-query")]
+    #[error("Failed to deserialize Query: {0}")]
+    FailedToDeserializeNestedQuery(#[source] serde_path_to_error::Error<serde_qs::Error>),
 
     #[error("No paths parameters found for matched route")]
     MissingPathParams,
 
-    #[error("Invalid UTF-8 in path parameters found for matched route")]
+    #[error("Invalid UTF-8 in path parameter `{key}`")]
     InvalidUtf8InPathParam { key: String },
 
     #[error("Failed to deserialize Path Params: {0}")]
-    FailedToDeserializePathParams(
-        #[source] serde_path_to_error::Error<serde_urlencoded::de::Error>,
-    ),
+    FailedToDeserializePathParams(#[source] PathError),
+
+    #[error("Validation failed: {0}")]
+    Validation(crate::validate::ValidationErrors),
+
+    #[error("No application state of type `{0}` was provided with `Router::with_state`")]
+    MissingAppState(&'static str),
+
+    #[error("Missing request header `{0}`")]
+    MissingHeader(&'static str),
+
+    #[error("Invalid request header `{name}`: {reason}")]
+    InvalidHeader { name: &'static str, reason: String },
 }
 
-impl IntoResponse for Error {
-    fn into_response(self) -> Response {
-        let status_code = match self {
+impl Error {
+    /// The status code of the response this error turns into.
+    pub fn status_code(&self) -> StatusCode {
+        match self {
             Self::JsonDataError(_) => StatusCode::UNPROCESSABLE_ENTITY,
             Self::JsonSyntaxError(_) => StatusCode::BAD_REQUEST,
             Self::UnknownBodyError(_) => StatusCode::BAD_REQUEST,
+            Self::PayloadTooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+            Self::InvalidUtf8Body => StatusCode::BAD_REQUEST,
             Self::InvalidJsonContentType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
             Self::InvalidFormContentType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            #[cfg(feature = "msgpack")]
+            Self::InvalidMsgPackContentType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            #[cfg(feature = "msgpack")]
+            Self::MsgPackError(err) => match err.inner() {
+                rmp_serde::decode::Error::Syntax(_)
+                | rmp_serde::decode::Error::TypeMismatch(_)
+                | rmp_serde::decode::Error::OutOfRange
+                | rmp_serde::decode::Error::LengthMismatch(_) => StatusCode::UNPROCESSABLE_ENTITY,
+                _ => StatusCode::BAD_REQUEST,
+            },
+            #[cfg(feature = "cbor")]
+            Self::InvalidCborContentType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            #[cfg(feature = "cbor")]
+            Self::CborError(ciborium::de::Error::Semantic(..)) => StatusCode::UNPROCESSABLE_ENTITY,
+            #[cfg(feature = "cbor")]
+            Self::CborError(_) => StatusCode::BAD_REQUEST,
+            #[cfg(feature = "ndjson")]
+            Self::InvalidNdJsonContentType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
             Self::FailedToDeserializeForm(_) => StatusCode::BAD_REQUEST,
             Self::FailedToDeserializeQuery(_) => StatusCode::BAD_REQUEST,
+            #[cfg(feature = "nested-query")]
+            Self::FailedToDeserializeNestedQuery(_) => StatusCode::BAD_REQUEST,
             Self::MissingPathParams => StatusCode::INTERNAL_SERVER_ERROR,
-            Self::InvalidUtf8InPathParam { key: _ } => StatusCode::INTERNAL_SERVER_ERROR,
-            Self::FailedToDeserializePathParams(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::InvalidUtf8InPathParam { key: _ } => StatusCode::BAD_REQUEST,
+            Self::FailedToDeserializePathParams(err) => err.status_code(),
+            Self::Validation(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            Self::MissingAppState(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::MissingHeader(_) => StatusCode::BAD_REQUEST,
+            Self::InvalidHeader { .. } => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
+impl Error {
+    /// The problem details of this error, with the failing field, parameter or header in the
+    /// `field` member.
+    pub fn problem(&self) -> ProblemDetails {
+        let field = match self {
+            Self::JsonDataError(err) => Some(err.path().to_string()),
+            #[cfg(feature = "msgpack")]
+            Self::MsgPackError(err) => Some(err.path().to_string()),
+            Self::FailedToDeserializeForm(err) => Some(err.path().to_string()),
+            Self::FailedToDeserializeQuery(err) => Some(err.path().to_string()),
+            #[cfg(feature = "nested-query")]
+            Self::FailedToDeserializeNestedQuery(err) => Some(err.path().to_string()),
+            Self::InvalidUtf8InPathParam { key } => Some(key.clone()),
+            Self::FailedToDeserializePathParams(err) => err.param().map(str::to_string),
+            Self::MissingHeader(name) | Self::InvalidHeader { name, .. } => Some(name.to_string()),
+            _ => None,
+        };
+        let mut problem = ProblemDetails::new(self.status_code()).with_detail(self.to_string());
+        if let Self::Validation(errors) = self {
+            problem = problem.with_extension("errors", errors.fields());
+        }
+        match field {
+            // `.` is the path of the root value, which isn't a field
+            Some(field) if field != "." => problem.with_extension("field", field),
+            _ => problem,
+        }
+    }
+}
+
+impl IntoResponse for Error {
+    fn into_response(self) -> Response {
+        let mut resp = (self.status_code(), self.to_string()).into_response();
+        resp.extensions_mut()
+            .insert(CaughtFailure::new(Failure::Rejection(self)));
+        resp
+    }
+}
+
+/// Why the path parameters of a request could not be deserialized into the type of a
+/// `Path<T>`.
+///
+/// Values the client sent that don't parse are `400 Bad Request`, while a type that doesn't fit
+/// the route, e.g. a field without a matching parameter, is a bug and `500 Internal Server
+/// Error`. Routes whose parameters should only match some values, and answer `404 Not Found`
+/// otherwise, can use constraints such as `{id:int}`.
+#[derive(ThisError, Debug)]
+pub enum PathError {
+    #[error("Cannot parse path parameter `{name}` with value `{value}` as `{expected}`")]
+    ParseParam {
+        name: String,
+        value: String,
+        expected: &'static str,
+    },
+
+    #[error("Invalid path parameter `{name}` with value `{value}`: {message}")]
+    InvalidParam {
+        name: String,
+        value: String,
+        message: String,
+    },
+
+    #[error("The route has no path parameter `{0}`")]
+    MissingParam(String),
+
+    #[error("Expected {expected} path parameters but the route has {got}")]
+    WrongNumberOfParams { expected: usize, got: usize },
+
+    #[error("Path parameters cannot be deserialized into `{0}`")]
+    UnsupportedType(&'static str),
+
+    #[error("{0}")]
+    Message(String),
+}
+
+impl PathError {
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            Self::ParseParam { .. } | Self::InvalidParam { .. } => StatusCode::BAD_REQUEST,
+            Self::MissingParam(_)
+            | Self::WrongNumberOfParams { .. }
+            | Self::UnsupportedType(_)
+            | Self::Message(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// The name of the parameter whose value was rejected, if any.
+    pub fn param(&self) -> Option<&str> {
+        match self {
+            Self::ParseParam { name, .. } | Self::InvalidParam { name, .. } => Some(name),
+            Self::MissingParam(name) => Some(name),
+            _ => None,
+        }
+    }
+}
+
+/// The rejection of an extractor derived with `#[derive(FromRequest)]`: the error of every
+/// field that failed to extract, not just the first one.
+#[derive(Debug, Default)]
+pub struct FieldErrors {
+    pub errors: Vec<(&'static str, Error)>,
+}
+
+impl FieldErrors {
+    pub fn push(&mut self, field: &'static str, error: Error) {
+        self.errors.push((field, error));
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// The status shared by every error, or `400 Bad Request` when they disagree. Server
+    /// errors, e.g. missing application state, take precedence.
+    pub fn status_code(&self) -> StatusCode {
+        let mut statuses = self.errors.iter().map(|(_, err)| err.status_code());
+        let Some(first) = statuses.next() else {
+            return StatusCode::BAD_REQUEST;
         };
-        (status_code, self.to_string()).into_response()
+        statuses.fold(first, |status, next| match (status, next) {
+            _ if status.is_server_error() => status,
+            _ if next.is_server_error() => next,
+            _ if status == next => status,
+            _ => StatusCode::BAD_REQUEST,
+        })
+    }
+}
+
+impl fmt::Display for FieldErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (field, err)) in self.errors.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "`{field}`: {err}")?;
+        }
+        Ok(())
+    }
+}
+
+impl StdError for FieldErrors {}
+
+impl FieldErrors {
+    /// The problem details of every error, in an `errors` member.
+    ///
+    /// The `field` member of each error is the path to the failing value from the struct, e.g.
+    /// `body.address.zip` when the `zip` of the `address` in the `body` field is invalid.
+    pub fn problem(&self) -> ProblemDetails {
+        let errors = self
+            .errors
+            .iter()
+            .map(|(field, err)| {
+                let mut problem = err.problem();
+                let path = match (err, problem.extensions.get("field")) {
+                    // the header name is not a path inside the field
+                    (Error::MissingHeader(_) | Error::InvalidHeader { .. }, _) => None,
+                    (_, Some(Value::String(inner))) => Some(format!("{field}.{inner}")),
+                    _ => None,
+                };
+                let path = path.unwrap_or_else(|| field.to_string());
+                problem.extensions.insert("field".to_string(), path.into());
+                problem
+            })
+            .collect::<Vec<_>>();
+        ProblemDetails::new(self.status_code())
+            .with_detail(self.to_string())
+            .with_extension("errors", errors)
+    }
+}
+
+impl IntoResponse for FieldErrors {
+    fn into_response(self) -> Response {
+        let mut resp = (self.status_code(), self.to_string()).into_response();
+        resp.extensions_mut()
+            .insert(CaughtFailure::new(Failure::FieldErrors(self)));
+        resp
     }
 }
 
@@ -73,6 +327,11 @@ impl BodyError {
         }
     }
 
+    /// Whether reading the body stopped because it crossed its size limit.
+    pub fn is_length_limit(&self) -> bool {
+        self.inner.is::<http_body_util::LengthLimitError>()
+    }
+
     /// Convert an `Error` back into the underlying boxed trait object.
     #[must_use]
     pub fn into_inner(self) -> BoxError {
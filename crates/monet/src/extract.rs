@@ -0,0 +1,345 @@
+//! Types that can be created from a [`Request`], to be taken as handler arguments.
+//!
+//! ```ignore
+//! async fn update_user(
+//!     Path(id): Path<u32>,
+//!     AppState(db): AppState<Db>,
+//!     Json(user): Json<User>,
+//! ) -> Result<Json<User>, Error> {
+//!     ...
+//! }
+//! ```
+//!
+//! Every argument but the last one has to implement [`FromRequestParts`], since only the last
+//! one may consume the request body. When an extractor fails, its rejection is turned into the
+//! response through [`IntoResponse`] and the handler is not called.
+
+#[cfg(test)]
+mod tests;
+
+use std::convert::Infallible;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use http::{HeaderMap, Method, Uri, Version};
+use serde_core::de::DeserializeOwned;
+
+use crate::{
+    body::BodyStream,
+    error::Error,
+    request::{Request, StateKey},
+    response::IntoResponse,
+    types::{AppState, Form, Json, Path, Query},
+};
+
+mod private {
+    #[derive(Debug, Clone, Copy)]
+    pub enum ViaParts {}
+
+    #[derive(Debug, Clone, Copy)]
+    pub enum ViaRequest {}
+}
+
+/// Extract a value from the request without consuming its body.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` cannot be extracted without consuming the request body",
+    label = "this handler argument",
+    note = "only the last argument of a handler may consume the body, e.g. `Json<T>`, `Form<T>`, `String` or `Bytes`"
+)]
+#[async_trait(?Send)]
+pub trait FromRequestParts: Sized {
+    /// The response sent back when the extraction fails.
+    type Rejection: IntoResponse;
+
+    /// Extract the value. Implementations must leave the body untouched.
+    async fn from_request_parts(req: &mut Request) -> Result<Self, Self::Rejection>;
+
+    /// Add the application state this extractor reads, checked when the server starts.
+    fn required_state(_keys: &mut Vec<StateKey>) {}
+}
+
+/// Extract a value from the whole request, possibly consuming its body.
+///
+/// The `M` parameter only tells apart the blanket implementation for [`FromRequestParts`]
+/// types, it never has to be named.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` cannot be extracted from a request",
+    label = "this handler argument",
+    note = "implement `FromRequestParts`, or `FromRequest` if it has to consume the body"
+)]
+#[async_trait(?Send)]
+pub trait FromRequest<M = private::ViaRequest>: Sized {
+    /// The response sent back when the extraction fails.
+    type Rejection: IntoResponse;
+
+    async fn from_request(req: Request) -> Result<Self, Self::Rejection>;
+
+    /// Add the application state this extractor reads, checked when the server starts.
+    fn required_state(_keys: &mut Vec<StateKey>) {}
+}
+
+#[async_trait(?Send)]
+impl<T> FromRequest<private::ViaParts> for T
+where
+    T: FromRequestParts,
+{
+    type Rejection = <Self as FromRequestParts>::Rejection;
+
+    async fn from_request(mut req: Request) -> Result<Self, Self::Rejection> {
+        T::from_request_parts(&mut req).await
+    }
+
+    fn required_state(keys: &mut Vec<StateKey>) {
+        <T as FromRequestParts>::required_state(keys);
+    }
+}
+
+#[async_trait(?Send)]
+impl FromRequest for Request {
+    type Rejection = Infallible;
+
+    async fn from_request(req: Request) -> Result<Self, Self::Rejection> {
+        Ok(req)
+    }
+}
+
+#[async_trait(?Send)]
+impl<T> FromRequestParts for Path<T>
+where
+    T: DeserializeOwned,
+{
+    type Rejection = Error;
+
+    async fn from_request_parts(req: &mut Request) -> Result<Self, Self::Rejection> {
+        req.path()
+    }
+}
+
+#[async_trait(?Send)]
+impl<T> FromRequestParts for Query<T>
+where
+    T: DeserializeOwned,
+{
+    type Rejection = Error;
+
+    async fn from_request_parts(req: &mut Request) -> Result<Self, Self::Rejection> {
+        req.query()
+    }
+}
+
+#[cfg(feature = "nested-query")]
+#[async_trait(?Send)]
+impl<T> FromRequestParts for crate::types::NestedQuery<T>
+where
+    T: DeserializeOwned,
+{
+    type Rejection = Error;
+
+    async fn from_request_parts(req: &mut Request) -> Result<Self, Self::Rejection> {
+        req.nested_query()
+    }
+}
+
+#[async_trait(?Send)]
+impl<T: 'static> FromRequestParts for AppState<T> {
+    type Rejection = Error;
+
+    async fn from_request_parts(req: &mut Request) -> Result<Self, Self::Rejection> {
+        __private::app_state(req)
+    }
+
+    fn required_state(keys: &mut Vec<StateKey>) {
+        keys.push(StateKey::of::<T>());
+    }
+}
+
+#[async_trait(?Send)]
+impl FromRequestParts for HeaderMap {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(req: &mut Request) -> Result<Self, Self::Rejection> {
+        Ok(req.headers().clone())
+    }
+}
+
+#[async_trait(?Send)]
+impl FromRequestParts for Method {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(req: &mut Request) -> Result<Self, Self::Rejection> {
+        Ok(req.method().clone())
+    }
+}
+
+#[async_trait(?Send)]
+impl FromRequestParts for Uri {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(req: &mut Request) -> Result<Self, Self::Rejection> {
+        Ok(req.uri().clone())
+    }
+}
+
+#[async_trait(?Send)]
+impl FromRequestParts for Version {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(req: &mut Request) -> Result<Self, Self::Rejection> {
+        Ok(*req.version())
+    }
+}
+
+#[async_trait(?Send)]
+impl<T> FromRequestParts for Option<T>
+where
+    T: FromRequestParts,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(req: &mut Request) -> Result<Self, Self::Rejection> {
+        Ok(T::from_request_parts(req).await.ok())
+    }
+}
+
+#[async_trait(?Send)]
+impl<T> FromRequestParts for Result<T, T::Rejection>
+where
+    T: FromRequestParts,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(req: &mut Request) -> Result<Self, Self::Rejection> {
+        Ok(T::from_request_parts(req).await)
+    }
+}
+
+#[async_trait(?Send)]
+impl FromRequest for BodyStream {
+    type Rejection = Infallible;
+
+    async fn from_request(req: Request) -> Result<Self, Self::Rejection> {
+        Ok(req.into_stream())
+    }
+}
+
+#[async_trait(?Send)]
+impl<T> FromRequest for Json<T>
+where
+    T: DeserializeOwned,
+{
+    type Rejection = Error;
+
+    async fn from_request(req: Request) -> Result<Self, Self::Rejection> {
+        req.into_json().await
+    }
+}
+
+#[cfg(feature = "msgpack")]
+#[async_trait(?Send)]
+impl<T> FromRequest for crate::types::MsgPack<T>
+where
+    T: DeserializeOwned,
+{
+    type Rejection = Error;
+
+    async fn from_request(req: Request) -> Result<Self, Self::Rejection> {
+        req.into_msgpack().await
+    }
+}
+
+#[cfg(feature = "cbor")]
+#[async_trait(?Send)]
+impl<T> FromRequest for crate::types::Cbor<T>
+where
+    T: DeserializeOwned,
+{
+    type Rejection = Error;
+
+    async fn from_request(req: Request) -> Result<Self, Self::Rejection> {
+        req.into_cbor().await
+    }
+}
+
+#[cfg(feature = "ndjson")]
+#[async_trait(?Send)]
+impl<T> FromRequest for crate::types::NdJson<crate::types::NdJsonStream<T>>
+where
+    T: DeserializeOwned,
+{
+    type Rejection = Error;
+
+    async fn from_request(req: Request) -> Result<Self, Self::Rejection> {
+        req.into_ndjson()
+    }
+}
+
+#[async_trait(?Send)]
+impl<T> FromRequest for Form<T>
+where
+    T: DeserializeOwned,
+{
+    type Rejection = Error;
+
+    async fn from_request(req: Request) -> Result<Self, Self::Rejection> {
+        req.into_form().await
+    }
+}
+
+#[async_trait(?Send)]
+impl FromRequest for Bytes {
+    type Rejection = Error;
+
+    async fn from_request(req: Request) -> Result<Self, Self::Rejection> {
+        req.into_bytes().await
+    }
+}
+
+#[async_trait(?Send)]
+impl FromRequest for String {
+    type Rejection = Error;
+
+    async fn from_request(req: Request) -> Result<Self, Self::Rejection> {
+        let bytes = req.into_bytes().await?;
+        String::from_utf8(bytes.into()).map_err(|_| Error::InvalidUtf8Body)
+    }
+}
+
+/// Helpers for the code generated by `#[derive(FromRequest)]`.
+#[doc(hidden)]
+pub mod __private {
+    use std::{fmt::Display, str::FromStr};
+
+    use crate::{error::Error, request::Request, types::AppState};
+
+    pub fn header<T>(req: &Request, name: &'static str) -> Result<T, Error>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        optional_header(req, name)?.ok_or(Error::MissingHeader(name))
+    }
+
+    pub fn app_state<T: 'static>(req: &Request) -> Result<AppState<T>, Error> {
+        req.app_state
+            .get_rc()
+            .map(AppState)
+            .ok_or(Error::MissingAppState(std::any::type_name::<T>()))
+    }
+
+    pub fn optional_header<T>(req: &Request, name: &'static str) -> Result<Option<T>, Error>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        let Some(value) = req.headers().get(name) else {
+            return Ok(None);
+        };
+        let invalid = |reason: String| Error::InvalidHeader { name, reason };
+        value
+            .to_str()
+            .map_err(|err| invalid(err.to_string()))?
+            .parse()
+            .map(Some)
+            .map_err(|err: T::Err| invalid(err.to_string()))
+    }
+}
@@ -0,0 +1,459 @@
+use bytes::Bytes;
+use http::{HeaderMap, Method, StatusCode, header};
+use http_body_util::{BodyExt, Full};
+use serde::Deserialize;
+
+use crate::{
+    AppState, Json, Path, Query, Router, body::Body, get, post, request::StateKey,
+    router::tests::request,
+};
+
+#[derive(Deserialize)]
+struct Pagination {
+    page: u32,
+}
+
+#[derive(Deserialize)]
+struct UserId {
+    id: u32,
+}
+
+#[derive(Deserialize)]
+struct User {
+    name: String,
+}
+
+struct Greeting(&'static str);
+
+async fn body_string(app: &Router, req: crate::Request) -> (StatusCode, String) {
+    let resp = app.handle(req).await;
+    let status = resp.status();
+    let body = resp.into_body().collect().await.unwrap().to_bytes();
+    (status, String::from_utf8(body.to_vec()).unwrap())
+}
+
+#[compio::test]
+async fn multiple_extractors() {
+    async fn update(
+        Path(UserId { id }): Path<UserId>,
+        Query(page): Query<Pagination>,
+        AppState(greeting): AppState<Greeting>,
+        Json(user): Json<User>,
+    ) -> String {
+        format!("{} {} #{id} p{}", greeting.0, user.name, page.page)
+    }
+
+    let app = Router::new()
+        .at("/user/{id}", post(update))
+        .with_state(Greeting("hello"));
+
+    let mut req = request(Method::POST, "/user/7?page=2");
+    req.headers_mut()
+        .insert(header::CONTENT_TYPE, "application/json".parse().unwrap());
+    req.body = Body::new(Full::new(r#"{"name":"ferris"}"#.into()));
+
+    let (status, body) = body_string(&app, req).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, "hello ferris #7 p2");
+}
+
+#[compio::test]
+async fn rejection_short_circuits() {
+    async fn page(Query(page): Query<Pagination>) -> String {
+        page.page.to_string()
+    }
+
+    let app = Router::new().at("/", get(page));
+
+    let (status, _) = body_string(&app, request(Method::GET, "/?page=abc")).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, body) = body_string(&app, request(Method::GET, "/?page=3")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, "3");
+}
+
+#[compio::test]
+async fn optional_and_parts_extractors() {
+    async fn inspect(
+        method: Method,
+        headers: HeaderMap,
+        greeting: Option<AppState<Greeting>>,
+    ) -> String {
+        format!(
+            "{method} {} {}",
+            headers.len(),
+            greeting.map_or("none", |g| g.0.0)
+        )
+    }
+
+    let app = Router::new().at("/", get(inspect));
+    let (_, body) = body_string(&app, request(Method::GET, "/")).await;
+    assert_eq!(body, "GET 0 none");
+}
+
+#[compio::test]
+async fn string_body() {
+    async fn echo(body: String) -> String {
+        body
+    }
+
+    let app = Router::new().at("/", post(echo));
+
+    let mut req = request(Method::POST, "/");
+    req.body = Body::new(Full::new("ping".into()));
+    assert_eq!(body_string(&app, req).await.1, "ping");
+
+    let mut req = request(Method::POST, "/");
+    req.body = Body::new(Full::new(vec![0xff, 0xfe].into()));
+    assert_eq!(body_string(&app, req).await.0, StatusCode::BAD_REQUEST);
+}
+
+#[test]
+fn extractors_declare_state() {
+    async fn greet(AppState(greeting): AppState<Greeting>) -> &'static str {
+        greeting.0
+    }
+
+    let app = Router::new().at("/", get(greet));
+    assert_eq!(app.missing_state(), vec![StateKey::of::<Greeting>()]);
+}
+
+/// Doc comments stay on the generated struct.
+#[crate::handler]
+async fn paged(
+    AppState(greeting): AppState<Greeting>,
+    Query(page): Query<Pagination>,
+    body: String,
+) -> String {
+    format!("{} {} {body}", greeting.0, page.page)
+}
+
+#[crate::handler]
+fn plain() -> &'static str {
+    "plain"
+}
+
+#[compio::test]
+async fn handler_macro() {
+    use crate::Endpoint;
+
+    assert_eq!(paged.name(), "paged");
+    assert_eq!(plain.name(), "plain");
+
+    let app = Router::new()
+        .at("/paged", post(paged))
+        .at("/plain", get(plain));
+    assert_eq!(app.missing_state(), vec![StateKey::of::<Greeting>()]);
+
+    let app = app.with_state(Greeting("hi"));
+    let mut req = request(Method::POST, "/paged?page=4");
+    req.body = Body::new(Full::new("there".into()));
+    assert_eq!(body_string(&app, req).await.1, "hi 4 there");
+    assert_eq!(
+        body_string(&app, request(Method::GET, "/plain")).await.1,
+        "plain"
+    );
+}
+
+#[derive(Deserialize)]
+struct NewOrder {
+    item: String,
+}
+
+struct Prefix(&'static str);
+
+#[derive(crate::FromRequest)]
+struct CreateOrder {
+    #[from(path)]
+    user: UserId,
+    #[from(query)]
+    page: Pagination,
+    #[from(header = "x-tenant")]
+    tenant: String,
+    #[from(header = "x-priority")]
+    priority: Option<u8>,
+    #[from(state)]
+    prefix: AppState<Prefix>,
+    #[from(json)]
+    order: NewOrder,
+}
+
+#[compio::test]
+async fn derived_extractor() {
+    async fn create(order: CreateOrder) -> String {
+        format!(
+            "{}{} {} {} {:?} {}",
+            order.prefix.0.0,
+            order.user.id,
+            order.page.page,
+            order.tenant,
+            order.priority,
+            order.order.item
+        )
+    }
+
+    let app = Router::new().at("/user/{id}", post(create));
+    assert_eq!(app.missing_state(), vec![StateKey::of::<Prefix>()]);
+    let app = app.with_state(Prefix("#"));
+
+    let mut req = request(Method::POST, "/user/3?page=1");
+    req.headers_mut()
+        .insert("x-tenant", "acme".parse().unwrap());
+    req.headers_mut()
+        .insert(header::CONTENT_TYPE, "application/json".parse().unwrap());
+    req.body = Body::new(Full::new(r#"{"item":"tea"}"#.into()));
+    let (status, body) = body_string(&app, req).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, "#3 1 acme None tea");
+
+    // every failing field is reported
+    let mut req = request(Method::POST, "/user/3?page=x");
+    req.headers_mut()
+        .insert("x-priority", "high".parse().unwrap());
+    let (status, body) = body_string(&app, req).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let fields = body
+        .lines()
+        .map(|line| line.split(':').next().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(fields, ["`page`", "`tenant`", "`priority`", "`order`"]);
+}
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct Address {
+    zip: u32,
+}
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct Signup {
+    address: Address,
+}
+
+#[derive(crate::FromRequest)]
+#[allow(dead_code)]
+struct SignupRequest {
+    #[from(header = "x-tenant")]
+    tenant: String,
+    #[from(json)]
+    body: Signup,
+}
+
+#[compio::test]
+async fn derived_extractor_nested_field() {
+    use crate::extract::FromRequest;
+
+    let mut req = request(Method::POST, "/signup");
+    req.headers_mut()
+        .insert(header::CONTENT_TYPE, "application/json".parse().unwrap());
+    req.body = Body::new(Full::new(r#"{"address":{"zip":"x"}}"#.into()));
+
+    let problem = SignupRequest::from_request(req)
+        .await
+        .err()
+        .unwrap()
+        .problem();
+    let fields = problem.extensions["errors"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|error| error["field"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(fields, ["tenant", "body.address.zip"]);
+}
+
+#[compio::test]
+async fn body_limits() {
+    use crate::handler::middleware::body_limit::{DEFAULT_BODY_LIMIT, DefaultBodyLimit};
+
+    async fn length(body: Bytes) -> String {
+        body.len().to_string()
+    }
+
+    let app = Router::new()
+        .at("/small", post(length))
+        .wrap_by(DefaultBodyLimit::max(4))
+        .at("/default", post(length))
+        .at("/unlimited", post(length).wrap(DefaultBodyLimit::disable()));
+    let status = async |uri, len: usize, content_length: bool| {
+        let mut req = request(Method::POST, uri);
+        if content_length {
+            req.headers_mut()
+                .insert(header::CONTENT_LENGTH, len.to_string().parse().unwrap());
+        }
+        req.body = Body::new(Full::new(Bytes::from(vec![b'x'; len])));
+        app.handle(req).await.status()
+    };
+
+    assert_eq!(status("/small", 4, true).await, StatusCode::OK);
+    assert_eq!(
+        status("/small", 5, true).await,
+        StatusCode::PAYLOAD_TOO_LARGE
+    );
+    assert_eq!(
+        status("/small", 5, false).await,
+        StatusCode::PAYLOAD_TOO_LARGE
+    );
+
+    let over_default = DEFAULT_BODY_LIMIT + 1;
+    assert_eq!(
+        status("/default", over_default, false).await,
+        StatusCode::PAYLOAD_TOO_LARGE
+    );
+    assert_eq!(
+        status("/unlimited", over_default, true).await,
+        StatusCode::OK
+    );
+}
+
+#[compio::test]
+async fn path_rejections() {
+    #[derive(Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    enum State {
+        Open,
+        Closed,
+    }
+
+    #[derive(Deserialize)]
+    struct Misnamed {
+        #[allow(dead_code)]
+        user_id: u32,
+    }
+
+    async fn single(Path(id): Path<u32>) -> String {
+        id.to_string()
+    }
+    async fn pair(Path((name, id)): Path<(String, u32)>) -> String {
+        format!("{name}:{id}")
+    }
+    async fn state(Path(state): Path<State>) -> &'static str {
+        match state {
+            State::Open => "open",
+            State::Closed => "closed",
+        }
+    }
+    async fn misnamed(_: Path<Misnamed>) {}
+    async fn no_params(_: Path<u32>) {}
+
+    let app = Router::new()
+        .at("/user/{id}", get(single))
+        .at("/team/{name}/{id}", get(pair))
+        .at("/issues/{state}", get(state))
+        .at("/misnamed/{id}", get(misnamed))
+        .at("/none", get(no_params));
+    let get = |uri| body_string(&app, request(Method::GET, uri));
+
+    assert_eq!(get("/user/42").await, (StatusCode::OK, "42".to_string()));
+    assert_eq!(
+        get("/user/abc").await,
+        (
+            StatusCode::BAD_REQUEST,
+            "Failed to deserialize Path Params: Cannot parse path parameter `id` with value `abc` \
+             as `u32`"
+                .to_string()
+        )
+    );
+    assert_eq!(
+        get("/team/a%20b/7").await,
+        (StatusCode::OK, "a b:7".to_string())
+    );
+    assert_eq!(get("/team/ab/x").await.0, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        get("/issues/open").await,
+        (StatusCode::OK, "open".to_string())
+    );
+
+    let (status, body) = get("/issues/merged").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("Invalid path parameter `state` with value `merged`"));
+
+    assert_eq!(get("/user/%FF").await.0, StatusCode::BAD_REQUEST);
+
+    // The route and the type disagree: a bug in the app, not in the request.
+    assert_eq!(
+        get("/misnamed/1").await.0,
+        StatusCode::INTERNAL_SERVER_ERROR
+    );
+    assert_eq!(get("/none").await.0, StatusCode::INTERNAL_SERVER_ERROR);
+}
+
+#[test]
+fn path_error_details() {
+    use crate::error::{Error, PathError};
+
+    let mut req = request(Method::GET, "/");
+    req.extensions_mut()
+        .insert(crate::router::url::UrlParams::Params(vec![(
+            "id".into(),
+            "abc".into(),
+        )]));
+    let Err(Error::FailedToDeserializePathParams(err)) = req.path::<u32>() else {
+        panic!("expected a path rejection");
+    };
+    assert_eq!(err.param(), Some("id"));
+    assert!(matches!(
+        err,
+        PathError::ParseParam {
+            expected: "u32",
+            ..
+        }
+    ));
+}
+
+#[compio::test]
+async fn body_stream() {
+    use compio::io::AsyncReadExt;
+    use futures::StreamExt;
+    use http_body::Frame;
+
+    use crate::{body::BodyStream, handler::middleware::body_limit::DefaultBodyLimit};
+
+    async fn chunks(mut body: BodyStream) -> String {
+        let mut chunks = Vec::new();
+        while let Some(chunk) = body.next().await {
+            match chunk {
+                Ok(chunk) => chunks.push(String::from_utf8(chunk.to_vec()).unwrap()),
+                Err(err) => return err.to_string(),
+            }
+        }
+        let checksum = body
+            .trailers()
+            .and_then(|trailers| trailers.get("checksum"));
+        format!("{chunks:?} {checksum:?}")
+    }
+
+    let with_frames = |uri| {
+        let mut trailers = HeaderMap::new();
+        trailers.insert("checksum", "abc".parse().unwrap());
+        let frames = [
+            Frame::data(Bytes::from("he")),
+            Frame::data(Bytes::new()),
+            Frame::data(Bytes::from("llo")),
+            Frame::trailers(trailers),
+        ];
+        let mut req = request(Method::POST, uri);
+        req.body = Body::new(http_body_util::StreamBody::new(futures::stream::iter(
+            frames.map(Ok::<_, std::convert::Infallible>),
+        )));
+        req
+    };
+
+    let app = Router::new()
+        .at("/", post(chunks))
+        .at("/small", post(chunks).wrap(DefaultBodyLimit::max(4)));
+    assert_eq!(
+        body_string(&app, with_frames("/")).await,
+        (StatusCode::OK, r#"["he", "llo"] Some("abc")"#.to_string())
+    );
+    let (_, message) = body_string(&app, with_frames("/small")).await;
+    assert!(message.contains("4"), "{message}");
+
+    let mut reader = with_frames("/").into_reader();
+    let compio::BufResult(read, data) = reader.read_to_end(Vec::new()).await;
+    read.unwrap();
+    assert_eq!(data, b"hello");
+    assert_eq!(reader.trailers().unwrap()["checksum"], "abc");
+}
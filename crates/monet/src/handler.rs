@@ -1,12 +1,13 @@
 pub mod endpoint;
 pub mod middleware;
 
-use std::rc::Rc;
+use std::{marker::PhantomData, rc::Rc};
 
 use async_trait::async_trait;
 
 use crate::{
-    request::Request,
+    extract::{FromRequest, FromRequestParts},
+    request::{Request, StateKey},
     response::{IntoResponse, Response},
 };
 
@@ -18,6 +19,11 @@ pub trait Middleware: 'static {
     fn name(&self) -> &str {
         std::any::type_name::<Self>()
     }
+
+    /// The application state this middleware reads, checked when the server starts.
+    fn required_state(&self) -> Vec<StateKey> {
+        Vec::new()
+    }
 }
 
 impl std::fmt::Debug for dyn Middleware {
@@ -46,6 +52,18 @@ pub trait Endpoint: 'static {
     fn name(&self) -> &str {
         std::any::type_name::<Self>()
     }
+
+    /// The application state this endpoint reads, checked when the server starts.
+    fn required_state(&self) -> Vec<StateKey> {
+        Vec::new()
+    }
+
+    /// The description of this endpoint in the OpenAPI document, see
+    /// [`DocumentedHandler`](crate::openapi::DocumentedHandler).
+    #[cfg(feature = "openapi")]
+    fn operation(&self) -> Option<&crate::openapi::Operation> {
+        None
+    }
 }
 
 impl std::fmt::Debug for dyn Endpoint {
@@ -54,18 +72,150 @@ impl std::fmt::Debug for dyn Endpoint {
     }
 }
 
+/// Anything that can be turned into an [`Endpoint`]: an `Endpoint` itself, or an async
+/// function whose arguments are all extractors and whose output implements [`IntoResponse`].
+///
+/// Every argument but the last one must implement [`FromRequestParts`]; the last one may be
+/// any [`FromRequest`]. Functions can take up to 16 arguments.
+///
+/// `T` only tells the implementations apart so that the compiler can pick one, it never has
+/// to be named.
+pub trait Handler<T>: Sized + 'static {
+    fn into_endpoint(self) -> Rc<dyn Endpoint>;
+}
+
+#[doc(hidden)]
+#[derive(Debug, Clone, Copy)]
+pub enum IsEndpoint {}
+
+impl<E: Endpoint> Handler<IsEndpoint> for E {
+    fn into_endpoint(self) -> Rc<dyn Endpoint> {
+        Rc::new(self)
+    }
+}
+
+/// The [`Endpoint`] made out of a [`Handler`] function.
+struct HandlerFn<F, T> {
+    f: F,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<F, Fut, Resp> Handler<()> for F
+where
+    F: 'static + Fn() -> Fut,
+    Fut: Future<Output = Resp>,
+    Resp: IntoResponse,
+{
+    fn into_endpoint(self) -> Rc<dyn Endpoint> {
+        Rc::new(HandlerFn {
+            f: self,
+            _marker: PhantomData,
+        })
+    }
+}
+
 #[async_trait(?Send)]
-impl<F, Fut, Resp> Endpoint for F
+impl<F, Fut, Resp> Endpoint for HandlerFn<F, ()>
 where
-    F: 'static + Fn(Request) -> Fut,
+    F: 'static + Fn() -> Fut,
     Fut: Future<Output = Resp>,
     Resp: IntoResponse,
 {
-    async fn call(&self, req: Request) -> Response {
-        (self)(req).await.into_response()
+    async fn call(&self, _req: Request) -> Response {
+        (self.f)().await.into_response()
+    }
+
+    fn name(&self) -> &str {
+        std::any::type_name::<F>()
     }
 }
 
+macro_rules! impl_handler {
+    ([$($ty:ident),*], $last:ident) => {
+        impl<F, Fut, Resp, M, $($ty,)* $last> Handler<(M, $($ty,)* $last,)> for F
+        where
+            F: 'static + Fn($($ty,)* $last) -> Fut,
+            Fut: Future<Output = Resp>,
+            Resp: IntoResponse,
+            M: 'static,
+            $($ty: FromRequestParts + 'static,)*
+            $last: FromRequest<M> + 'static,
+        {
+            fn into_endpoint(self) -> Rc<dyn Endpoint> {
+                Rc::new(HandlerFn {
+                    f: self,
+                    _marker: PhantomData,
+                })
+            }
+        }
+
+        #[async_trait(?Send)]
+        impl<F, Fut, Resp, M, $($ty,)* $last> Endpoint for HandlerFn<F, (M, $($ty,)* $last,)>
+        where
+            F: 'static + Fn($($ty,)* $last) -> Fut,
+            Fut: Future<Output = Resp>,
+            Resp: IntoResponse,
+            M: 'static,
+            $($ty: FromRequestParts + 'static,)*
+            $last: FromRequest<M> + 'static,
+        {
+            #[allow(non_snake_case, unused_mut)]
+            async fn call(&self, mut req: Request) -> Response {
+                $(
+                    let $ty = match <$ty as FromRequestParts>::from_request_parts(&mut req).await {
+                        Ok(value) => value,
+                        Err(rejection) => return rejection.into_response(),
+                    };
+                )*
+                let $last = match <$last as FromRequest<M>>::from_request(req).await {
+                    Ok(value) => value,
+                    Err(rejection) => return rejection.into_response(),
+                };
+                (self.f)($($ty,)* $last).await.into_response()
+            }
+
+            fn name(&self) -> &str {
+                std::any::type_name::<F>()
+            }
+
+            fn required_state(&self) -> Vec<StateKey> {
+                let mut keys = Vec::new();
+                $(<$ty as FromRequestParts>::required_state(&mut keys);)*
+                <$last as FromRequest<M>>::required_state(&mut keys);
+                keys
+            }
+        }
+    };
+}
+
+impl_handler!([], T1);
+impl_handler!([T1], T2);
+impl_handler!([T1, T2], T3);
+impl_handler!([T1, T2, T3], T4);
+impl_handler!([T1, T2, T3, T4], T5);
+impl_handler!([T1, T2, T3, T4, T5], T6);
+impl_handler!([T1, T2, T3, T4, T5, T6], T7);
+impl_handler!([T1, T2, T3, T4, T5, T6, T7], T8);
+impl_handler!([T1, T2, T3, T4, T5, T6, T7, T8], T9);
+impl_handler!([T1, T2, T3, T4, T5, T6, T7, T8, T9], T10);
+impl_handler!([T1, T2, T3, T4, T5, T6, T7, T8, T9, T10], T11);
+impl_handler!([T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11], T12);
+impl_handler!([T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12], T13);
+impl_handler!(
+    [T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13],
+    T14
+);
+impl_handler!(
+    [T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14],
+    T15
+);
+impl_handler!(
+    [
+        T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15
+    ],
+    T16
+);
+
 #[derive(Clone, Debug)]
 pub struct Layer {
     pub(crate) middlewares: Vec<Rc<dyn Middleware>>,
@@ -73,10 +223,10 @@ pub struct Layer {
 }
 
 impl Layer {
-    pub fn new(endpoint: impl Endpoint) -> Self {
+    pub fn new<T>(handler: impl Handler<T>) -> Self {
         Layer {
             middlewares: Default::default(),
-            endpoint: Rc::new(endpoint),
+            endpoint: handler.into_endpoint(),
         }
     }
 
@@ -84,6 +234,13 @@ impl Layer {
         self.middlewares.push(m.clone());
     }
 
+    pub(crate) fn required_state(&self) -> impl Iterator<Item = StateKey> {
+        self.middlewares
+            .iter()
+            .flat_map(|m| m.required_state())
+            .chain(self.endpoint.required_state())
+    }
+
     pub async fn next(mut self, req: Request) -> Response {
         if let Some(current) = self.middlewares.pop() {
             current.transform(req, self).await
@@ -1,3 +1,6 @@
+#[cfg(test)]
+mod tests;
+
 use std::{
     path::{Component, Path, PathBuf},
     time::SystemTime,
@@ -60,6 +63,13 @@ impl Endpoint for ServeDir {
 
         match open_file(req, path, buf_size, append).await {
             Ok(OpenFileOutput::FileOpened(file_output)) => build_response(*file_output).await,
+            Ok(OpenFileOutput::Redirect(location)) => {
+                let mut resp = StatusCode::TEMPORARY_REDIRECT.into_response();
+                if let Ok(location) = HeaderValue::from_str(&location) {
+                    resp.headers_mut().insert(header::LOCATION, location);
+                }
+                resp
+            }
             Err(e) => panic!("normal error {e}"),
             _ => panic!("fetal error"),
         }
@@ -280,8 +290,3 @@ pub(crate) struct FileOpened {
     pub(super) mime: HeaderValue,
     pub(super) last_modified: Option<HttpDate>,
 }
-
-pub(crate) enum FileRequestExtent {
-    Full(File, u64),
-    Head(u64),
-}
@@ -0,0 +1,15 @@
+use http::{Method, StatusCode, header};
+
+use crate::{Endpoint, ServeDir, router::tests::request};
+
+#[compio::test]
+async fn redirects_directories_to_trailing_slash() {
+    let dir = ServeDir::new("src");
+
+    let resp = dir.call(request(Method::GET, "/handler?page=2")).await;
+    assert_eq!(resp.status(), StatusCode::TEMPORARY_REDIRECT);
+    assert_eq!(resp.headers()[header::LOCATION], "/handler/?page=2");
+
+    let resp = dir.call(request(Method::POST, "/handler")).await;
+    assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+}
@@ -1,2 +1,3 @@
+pub mod body_limit;
 pub mod catch_panic;
 pub mod strip_prefix;
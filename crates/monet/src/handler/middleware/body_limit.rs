@@ -0,0 +1,48 @@
+use async_trait::async_trait;
+
+use crate::{Layer, Middleware, Request, Response};
+
+/// The maximum size of a request body buffered by [`Request::into_bytes`] and the extractors
+/// built on it, unless overridden with [`DefaultBodyLimit`]: 2 MiB.
+pub const DEFAULT_BODY_LIMIT: usize = 2 * 1024 * 1024;
+
+/// Override the body size limit of the requests going through it.
+///
+/// Bodies announcing a larger `Content-Length` are rejected before being read, and streamed
+/// bodies are cut off as soon as they cross the limit, both with `413 Payload Too Large`.
+///
+/// ```ignore
+/// let app = Router::new()
+///     .at("/upload", post(upload).wrap(DefaultBodyLimit::max(100 * 1024 * 1024)))
+///     .at("/login", post(login))
+///     // every route added so far, except where a route sets its own limit
+///     .wrap_by(DefaultBodyLimit::max(16 * 1024));
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct DefaultBodyLimit {
+    limit: Option<usize>,
+}
+
+impl DefaultBodyLimit {
+    /// Limit bodies to `limit` bytes.
+    pub fn max(limit: usize) -> Self {
+        Self { limit: Some(limit) }
+    }
+
+    /// Don't limit the size of bodies.
+    pub fn disable() -> Self {
+        Self { limit: None }
+    }
+}
+
+#[async_trait(?Send)]
+impl Middleware for DefaultBodyLimit {
+    async fn transform(&self, mut req: Request, layer: Layer) -> Response {
+        req.extensions_mut().insert(BodyLimit(self.limit));
+        layer.next(req).await
+    }
+}
+
+/// The body size limit of a request, set by the innermost [`DefaultBodyLimit`].
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct BodyLimit(pub(crate) Option<usize>);
@@ -4,7 +4,10 @@ use async_trait::async_trait;
 use futures_util::FutureExt;
 use http::StatusCode;
 
-use crate::{IntoResponse, Layer, Middleware, Request, Response};
+use crate::{
+    IntoResponse, Layer, Middleware, Request, Response,
+    router::error_hook::{CaughtFailure, Failure},
+};
 
 #[derive(Default, Debug)]
 pub struct CatchPanic;
@@ -18,8 +21,16 @@ impl Middleware for CatchPanic {
             .unwrap_or_else(|err| {
                 tracing::error!(error = ?err, "panic occurred");
 
+                let message = match err.downcast::<String>() {
+                    Ok(message) => *message,
+                    Err(err) => err
+                        .downcast_ref::<&str>()
+                        .map_or_else(|| "Box<dyn Any>".to_string(), |s| s.to_string()),
+                };
                 let mut resp = "Service panicked".into_response();
                 *resp.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+                resp.extensions_mut()
+                    .insert(CaughtFailure::new(Failure::Panic(message)));
                 resp
             })
     }
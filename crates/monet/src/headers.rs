@@ -0,0 +1,8 @@
+//! Typed headers, to be used with [`TypedHeader`](crate::TypedHeader).
+//!
+//! Re-exports the [`headers`](::headers) crate, plus the [`Accept`] and [`LastEventId`] headers
+//! it lacks.
+
+pub use headers::*;
+
+pub use crate::types::typed_header::{Accept, LastEventId};
@@ -1,24 +1,50 @@
 pub mod body;
+pub mod cookie;
 pub mod error;
+pub mod extract;
 pub mod handler;
+pub mod headers;
+#[cfg(feature = "multipart")]
+pub mod multipart;
+#[cfg(feature = "openapi")]
+pub mod openapi;
+pub mod problem;
 pub mod request;
 pub mod response;
 pub mod router;
 pub mod serve;
 pub mod types;
+pub mod validate;
+
+// Lets the code generated by `monet-macros` name `monet` from within this crate too.
+extern crate self as monet;
 
 pub use async_trait::async_trait;
+pub use http;
 
-// pub use monet_macros::handler;
+#[cfg(feature = "cbor")]
+pub use crate::types::Cbor;
+#[cfg(feature = "msgpack")]
+pub use crate::types::MsgPack;
+#[cfg(feature = "nested-query")]
+pub use crate::types::NestedQuery;
+#[cfg(feature = "ndjson")]
+pub use crate::types::{NdJson, NdJsonStream};
 pub use crate::{
     error::{BodyError, BoxError, Error},
-    handler::middleware::catch_panic::CatchPanic,
-    handler::{Endpoint, Layer, Middleware, endpoint::serve_dir::ServeDir},
+    extract::{FromRequest, FromRequestParts},
+    handler::middleware::{body_limit::DefaultBodyLimit, catch_panic::CatchPanic},
+    handler::{Endpoint, Handler, Layer, Middleware, endpoint::serve_dir::ServeDir},
+    problem::{ProblemDetails, ProblemJson},
     request::Request,
-    response::{IntoResponse, Response},
-    router::{Router, get, post},
-    serve::run,
-    types::{Form, Json, Path},
+    response::{IntoResponse, Negotiate, Response, Sse, StreamBody},
+    router::{RouteEndpoint, Router, get, post},
+    serve::{Server, mesh::Mesh, run},
+    types::{AppState, Form, Json, Path, Query, TypedHeader},
+    validate::{Valid, Validate},
+};
+pub use monet_macros::{
+    FromRequest, IntoResponse, delete, get, handler, head, options, patch, post, put,
 };
 
 pub(crate) const GUARANTEE: &str = "Should never fail. Please file a bug if it does";
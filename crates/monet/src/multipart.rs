@@ -0,0 +1,245 @@
+//! `multipart/form-data` parsing, e.g. for file uploads.
+//!
+//! [`Multipart`] reads the body incrementally: fields are yielded one after the other, and the
+//! content of each is itself a stream of chunks, so uploads never have to be held in memory.
+//!
+//! ```ignore
+//! async fn upload(mut multipart: Multipart) -> Result<String, MultipartError> {
+//!     while let Some(field) = multipart.next_field().await? {
+//!         if field.name() == Some("avatar") {
+//!             let size = field.save_to("/tmp/avatar.png").await?;
+//!             return Ok(format!("saved {size} bytes"));
+//!         }
+//!     }
+//!     Ok("no avatar".to_string())
+//! }
+//! ```
+//!
+//! The sizes of fields and of the whole body can be limited by providing [`MultipartLimits`] as
+//! application state, or by building the parser with [`Multipart::with_limits`]. The body is
+//! also subject to the request's [`DefaultBodyLimit`].
+//!
+//! [`DefaultBodyLimit`]: crate::handler::middleware::body_limit::DefaultBodyLimit
+
+#[cfg(test)]
+mod tests;
+
+use std::{
+    marker::PhantomData,
+    path::Path,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use compio::io::AsyncWriteAtExt;
+use futures::Stream;
+use http::{HeaderMap, StatusCode, header};
+use http_body_util::BodyDataStream;
+use mime::Mime;
+use thiserror::Error as ThisError;
+
+use crate::{
+    error::BodyError,
+    extract::FromRequest,
+    request::Request,
+    response::{IntoResponse, Response},
+};
+
+/// Size limits of a `multipart/form-data` body, in bytes. Nothing is limited by default.
+#[derive(Clone, Debug, Default)]
+pub struct MultipartLimits {
+    per_field: Option<u64>,
+    total: Option<u64>,
+}
+
+impl MultipartLimits {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Limit the size of the content of every field.
+    pub fn per_field(mut self, limit: u64) -> Self {
+        self.per_field = Some(limit);
+        self
+    }
+
+    /// Limit the size of the whole body.
+    pub fn total(mut self, limit: u64) -> Self {
+        self.total = Some(limit);
+        self
+    }
+
+    fn constraints(&self) -> multer::Constraints {
+        let mut size_limit = multer::SizeLimit::new();
+        if let Some(limit) = self.per_field {
+            size_limit = size_limit.per_field(limit);
+        }
+        if let Some(limit) = self.total {
+            size_limit = size_limit.whole_stream(limit);
+        }
+        multer::Constraints::new().size_limit(size_limit)
+    }
+}
+
+#[derive(ThisError, Debug)]
+pub enum MultipartError {
+    #[error("Multipart request must have `Content-Type: multipart/form-data` with a boundary")]
+    InvalidContentType,
+
+    #[error(transparent)]
+    Body(#[from] crate::Error),
+
+    #[error("Failed to parse the multipart body: {0}")]
+    Parse(#[from] multer::Error),
+
+    #[error("Failed to save the multipart field: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+impl IntoResponse for MultipartError {
+    fn into_response(self) -> Response {
+        let status_code = match &self {
+            Self::InvalidContentType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            Self::Body(err) => err.status_code(),
+            Self::Parse(
+                multer::Error::FieldSizeExceeded { .. } | multer::Error::StreamSizeExceeded { .. },
+            ) => StatusCode::PAYLOAD_TOO_LARGE,
+            Self::Parse(multer::Error::StreamReadFailed(err))
+                if err
+                    .downcast_ref::<BodyError>()
+                    .is_some_and(BodyError::is_length_limit) =>
+            {
+                StatusCode::PAYLOAD_TOO_LARGE
+            }
+            Self::Parse(_) => StatusCode::BAD_REQUEST,
+            Self::Io(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        (status_code, self.to_string()).into_response()
+    }
+}
+
+/// The fields of a `multipart/form-data` request, read one after the other.
+pub struct Multipart {
+    inner: multer::Multipart<'static>,
+}
+
+impl Multipart {
+    /// Parse the body of `req` within `limits`.
+    pub fn with_limits(req: Request, limits: &MultipartLimits) -> Result<Self, MultipartError> {
+        let boundary = boundary(req.headers()).ok_or(MultipartError::InvalidContentType)?;
+        let stream = BodyDataStream::new(req.into_limited_body()?);
+        Ok(Multipart {
+            inner: multer::Multipart::with_constraints(stream, boundary, limits.constraints()),
+        })
+    }
+
+    /// The next field, or `None` once the body is over. The previous field has to be dropped
+    /// first.
+    pub async fn next_field(&mut self) -> Result<Option<Field<'_>>, MultipartError> {
+        let field = self.inner.next_field().await?;
+        Ok(field.map(|inner| Field {
+            inner,
+            _multipart: PhantomData,
+        }))
+    }
+}
+
+impl std::fmt::Debug for Multipart {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Multipart").finish_non_exhaustive()
+    }
+}
+
+#[async_trait(?Send)]
+impl FromRequest for Multipart {
+    type Rejection = MultipartError;
+
+    async fn from_request(req: Request) -> Result<Self, Self::Rejection> {
+        let limits = req
+            .app_state
+            .get::<MultipartLimits>()
+            .cloned()
+            .unwrap_or_default();
+        Self::with_limits(req, &limits)
+    }
+}
+
+/// A field of a multipart body, whose content is a stream of [`Bytes`] chunks.
+#[derive(Debug)]
+pub struct Field<'a> {
+    inner: multer::Field<'static>,
+    // Fields have to be read in order, so only one may be borrowed from `Multipart` at a time.
+    _multipart: PhantomData<&'a mut Multipart>,
+}
+
+impl Field<'_> {
+    /// The `name` of the field in its `Content-Disposition` header.
+    pub fn name(&self) -> Option<&str> {
+        self.inner.name()
+    }
+
+    /// The `filename` of the field in its `Content-Disposition` header, set for file uploads.
+    ///
+    /// It comes straight from the client, so don't use it as a path without sanitizing it.
+    pub fn file_name(&self) -> Option<&str> {
+        self.inner.file_name()
+    }
+
+    pub fn content_type(&self) -> Option<&Mime> {
+        self.inner.content_type()
+    }
+
+    pub fn headers(&self) -> &HeaderMap {
+        self.inner.headers()
+    }
+
+    /// The next chunk of the content, or `None` once it is over.
+    pub async fn chunk(&mut self) -> Result<Option<Bytes>, MultipartError> {
+        Ok(self.inner.chunk().await?)
+    }
+
+    /// Buffer the whole content.
+    pub async fn bytes(self) -> Result<Bytes, MultipartError> {
+        Ok(self.inner.bytes().await?)
+    }
+
+    /// Buffer the whole content as text.
+    pub async fn text(self) -> Result<String, MultipartError> {
+        Ok(self.inner.text().await?)
+    }
+
+    /// Write the content to a new file at `path`, replacing any existing one, chunk by chunk.
+    /// Returns the number of bytes written.
+    pub async fn save_to(mut self, path: impl AsRef<Path>) -> Result<u64, MultipartError> {
+        let mut file = compio::fs::File::create(path).await?;
+        let mut written = 0;
+        while let Some(chunk) = self.chunk().await? {
+            let len = chunk.len() as u64;
+            file.write_all_at(chunk, written).await.0?;
+            written += len;
+        }
+        file.sync_data().await?;
+        Ok(written)
+    }
+}
+
+impl Stream for Field<'_> {
+    type Item = Result<Bytes, MultipartError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.inner)
+            .poll_next(cx)
+            .map(|chunk| chunk.map(|chunk| chunk.map_err(MultipartError::from)))
+    }
+}
+
+fn boundary(headers: &HeaderMap) -> Option<String> {
+    let content_type = headers.get(header::CONTENT_TYPE)?.to_str().ok()?;
+    let mime: Mime = content_type.parse().ok()?;
+    if mime.essence_str() != mime::MULTIPART_FORM_DATA.essence_str() {
+        return None;
+    }
+    multer::parse_boundary(content_type).ok()
+}
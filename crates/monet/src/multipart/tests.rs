@@ -0,0 +1,76 @@
+use http::{HeaderValue, Method, StatusCode, header};
+use http_body_util::{BodyExt, Full};
+
+use super::{Multipart, MultipartError, MultipartLimits};
+use crate::{Router, body::Body, post, router::tests::request};
+
+const BODY: &str = "--X\r\n\
+    Content-Disposition: form-data; name=\"title\"\r\n\r\n\
+    holiday\r\n\
+    --X\r\n\
+    Content-Disposition: form-data; name=\"photo\"; filename=\"beach.txt\"\r\n\
+    Content-Type: text/plain\r\n\r\n\
+    sand and sea\r\n\
+    --X--\r\n";
+
+fn multipart_request(content_type: &'static str) -> crate::Request {
+    let mut req = request(Method::POST, "/");
+    req.headers_mut()
+        .insert(header::CONTENT_TYPE, HeaderValue::from_static(content_type));
+    req.body = Body::new(Full::new(BODY.into()));
+    req
+}
+
+async fn upload(mut multipart: Multipart) -> Result<String, MultipartError> {
+    let mut out = Vec::new();
+    while let Some(field) = multipart.next_field().await? {
+        let name = field.name().unwrap_or_default().to_string();
+        match field.file_name().map(str::to_string) {
+            Some(file_name) => {
+                let mime = field.content_type().unwrap().to_string();
+                let path = std::env::temp_dir().join(format!("monet-{}", std::process::id()));
+                let size = field.save_to(&path).await?;
+                let saved = compio::fs::read(&path).await?;
+                compio::fs::remove_file(&path).await?;
+                out.push(format!(
+                    "{name}={file_name} ({mime}, {size}): {}",
+                    String::from_utf8_lossy(&saved)
+                ));
+            }
+            None => out.push(format!("{name}={}", field.text().await?)),
+        }
+    }
+    Ok(out.join("\n"))
+}
+
+#[compio::test]
+async fn multipart_fields() {
+    let app = Router::new().at("/", post(upload));
+    let resp = app
+        .handle(multipart_request("multipart/form-data; boundary=X"))
+        .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let body = resp.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(
+        body,
+        "title=holiday\nphoto=beach.txt (text/plain, 12): sand and sea"
+    );
+}
+
+#[compio::test]
+async fn multipart_rejections() {
+    let app = Router::new()
+        .at("/", post(upload))
+        .with_state(MultipartLimits::new().per_field(8));
+    let status = async |content_type| app.handle(multipart_request(content_type)).await.status();
+
+    assert_eq!(
+        status("multipart/form-data; boundary=X").await,
+        StatusCode::PAYLOAD_TOO_LARGE
+    );
+    assert_eq!(
+        status("application/x-www-form-urlencoded").await,
+        StatusCode::UNSUPPORTED_MEDIA_TYPE
+    );
+}
@@ -0,0 +1,741 @@
+//! OpenAPI 3.1 document generation, enabled by the `openapi` feature.
+//!
+//! Every handler of a route can carry an [`Operation`] describing it, see
+//! [`DocumentedHandler`]:
+//!
+//! ```ignore
+//! async fn show_user(Path(id): Path<UserId>, Query(q): Query<Fields>) -> Json<User> { .. }
+//!
+//! let app = Router::new()
+//!     .at(
+//!         "/users/{id:int}",
+//!         get(show_user.doc(
+//!             Operation::new()
+//!                 .summary("Fetch a user")
+//!                 .tag("users")
+//!                 .response::<Json<User>>(StatusCode::OK, "The user"),
+//!         )),
+//!     )
+//!     .serve_openapi("/openapi.json", Info::new("Users", "1.0.0"));
+//! ```
+//!
+//! The request inputs of an operation are derived from the extractors its handler takes,
+//! through [`OperationInput`]: the schemas of [`Json`], [`Form`], [`Query`] and [`Path`] come
+//! from [`schemars::JsonSchema`]. Path parameters that are not described by a `Path<T>` input
+//! are still listed, typed after their route [`Constraint`] if there is one.
+
+#[cfg(test)]
+mod tests;
+
+use std::rc::Rc;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use headers::Header;
+use http::{HeaderMap, HeaderValue, Method, StatusCode, Uri, Version, header::CONTENT_TYPE};
+pub use schemars;
+use schemars::{JsonSchema, Schema, SchemaGenerator, generate::SchemaSettings};
+use serde_json::{Map, Value, json};
+
+use crate::{
+    Endpoint, Handler, IntoResponse, Request, Response, Router,
+    body::BodyStream,
+    cookie::CookieJar,
+    headers::{Accept, LastEventId},
+    request::StateKey,
+    router::{Constraint, Route, url::NEST_TAIL_PARAM},
+    types::{AppState, Form, Html, Json, Path, Query, TypedHeader},
+    validate::Valid,
+};
+
+const OPENAPI_VERSION: &str = "3.1.0";
+
+const SCHEMAS_PATH: &str = "/components/schemas";
+
+/// The `info` object of the generated document.
+#[derive(Clone, Debug)]
+pub struct Info {
+    pub title: String,
+    pub version: String,
+    pub description: Option<String>,
+}
+
+impl Info {
+    pub fn new(title: impl Into<String>, version: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            version: version.into(),
+            description: None,
+        }
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+}
+
+type DescribeInput = fn(&mut SchemaGenerator, &mut Map<String, Value>);
+
+type DescribeOutput = fn(&mut SchemaGenerator) -> Option<(&'static str, Schema)>;
+
+/// Metadata of a single method on a route.
+#[derive(Clone, Debug, Default)]
+pub struct Operation {
+    summary: Option<String>,
+    description: Option<String>,
+    operation_id: Option<String>,
+    tags: Vec<String>,
+    deprecated: bool,
+    inputs: Vec<DescribeInput>,
+    responses: Vec<(StatusCode, String, DescribeOutput)>,
+}
+
+impl Operation {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn summary(mut self, summary: impl Into<String>) -> Self {
+        self.summary = Some(summary.into());
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn operation_id(mut self, id: impl Into<String>) -> Self {
+        self.operation_id = Some(id.into());
+        self
+    }
+
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    pub fn deprecated(mut self) -> Self {
+        self.deprecated = true;
+        self
+    }
+
+    /// Describe a request input the arguments of the handler don't show, e.g. one read by a
+    /// middleware or by a handler taking the whole [`Request`].
+    pub fn input<T: OperationInput>(mut self) -> Self {
+        self.inputs.push(T::describe);
+        self
+    }
+
+    /// Describe the response sent with `status`, e.g. `Json<T>` or `String`.
+    pub fn response<T: OperationOutput>(
+        mut self,
+        status: StatusCode,
+        description: impl Into<String>,
+    ) -> Self {
+        self.responses
+            .push((status, description.into(), T::describe));
+        self
+    }
+
+    fn to_value(&self, generator: &mut SchemaGenerator, path_params: &[PathParam]) -> Value {
+        let mut op = Map::new();
+        if let Some(summary) = &self.summary {
+            op.insert("summary".into(), summary.as_str().into());
+        }
+        if let Some(description) = &self.description {
+            op.insert("description".into(), description.as_str().into());
+        }
+        if let Some(id) = &self.operation_id {
+            op.insert("operationId".into(), id.as_str().into());
+        }
+        if !self.tags.is_empty() {
+            op.insert("tags".into(), self.tags.clone().into());
+        }
+        if self.deprecated {
+            op.insert("deprecated".into(), true.into());
+        }
+
+        self.inputs
+            .iter()
+            .for_each(|describe| describe(generator, &mut op));
+
+        // Path parameters are required by the spec, list those no `Path<T>` input described
+        let parameters = op
+            .entry("parameters")
+            .or_insert_with(|| Value::Array(vec![]))
+            .as_array_mut()
+            .expect(crate::GUARANTEE);
+        for param in path_params {
+            let described = parameters
+                .iter()
+                .any(|p| p["in"] == "path" && p["name"] == param.name);
+            if !described {
+                parameters.push(json!({
+                    "name": param.name,
+                    "in": "path",
+                    "required": true,
+                    "schema": param.schema,
+                }));
+            }
+        }
+        if parameters.is_empty() {
+            op.remove("parameters");
+        }
+
+        let mut responses = Map::new();
+        for (status, description, describe) in &self.responses {
+            let mut response = Map::new();
+            response.insert("description".into(), description.as_str().into());
+            if let Some((content_type, schema)) = describe(generator) {
+                response.insert(
+                    "content".into(),
+                    json!({ content_type: { "schema": schema } }),
+                );
+            }
+            responses.insert(status.as_str().into(), response.into());
+        }
+        if responses.is_empty() {
+            responses.insert("default".into(), json!({ "description": "" }));
+        }
+        op.insert("responses".into(), responses.into());
+
+        op.into()
+    }
+}
+
+/// A request input that can be described in an [`Operation`].
+///
+/// Every argument of a [`DocumentedHandler`] has to implement it. Extractors with nothing to
+/// document, such as [`AppState`], describe nothing.
+pub trait OperationInput {
+    fn describe(generator: &mut SchemaGenerator, operation: &mut Map<String, Value>);
+}
+
+/// A handler function whose [`Operation`] is documented along with it.
+pub trait DocumentedHandler<T>: Handler<T> {
+    /// Describe this handler in the generated document, e.g. `get(show_user.doc(operation))`.
+    ///
+    /// The inputs of the operation are derived from the types of the handler's arguments, so
+    /// the document follows the code. `operation` adds the responses and the rest.
+    fn doc(self, operation: Operation) -> Documented;
+}
+
+impl<F: Handler<()>> DocumentedHandler<()> for F {
+    fn doc(self, operation: Operation) -> Documented {
+        Documented {
+            endpoint: self.into_endpoint(),
+            operation,
+        }
+    }
+}
+
+macro_rules! impl_documented_handler {
+    ($($ty:ident),*) => {
+        impl<F, M, $($ty,)*> DocumentedHandler<(M, $($ty,)*)> for F
+        where
+            F: Handler<(M, $($ty,)*)>,
+            $($ty: OperationInput,)*
+        {
+            fn doc(self, mut operation: Operation) -> Documented {
+                let inputs: [DescribeInput; _] = [$(<$ty as OperationInput>::describe),*];
+                operation.inputs.splice(0..0, inputs);
+                Documented {
+                    endpoint: self.into_endpoint(),
+                    operation,
+                }
+            }
+        }
+    };
+}
+
+impl_documented_handler!(T1);
+impl_documented_handler!(T1, T2);
+impl_documented_handler!(T1, T2, T3);
+impl_documented_handler!(T1, T2, T3, T4);
+impl_documented_handler!(T1, T2, T3, T4, T5);
+impl_documented_handler!(T1, T2, T3, T4, T5, T6);
+impl_documented_handler!(T1, T2, T3, T4, T5, T6, T7);
+impl_documented_handler!(T1, T2, T3, T4, T5, T6, T7, T8);
+impl_documented_handler!(T1, T2, T3, T4, T5, T6, T7, T8, T9);
+impl_documented_handler!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10);
+impl_documented_handler!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11);
+impl_documented_handler!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12);
+impl_documented_handler!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13);
+impl_documented_handler!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14);
+impl_documented_handler!(
+    T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15
+);
+impl_documented_handler!(
+    T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15, T16
+);
+
+/// A handler with its [`Operation`], made by [`DocumentedHandler::doc`].
+#[derive(Debug)]
+pub struct Documented {
+    endpoint: Rc<dyn Endpoint>,
+    operation: Operation,
+}
+
+#[async_trait(?Send)]
+impl Endpoint for Documented {
+    async fn call(&self, req: Request) -> Response {
+        self.endpoint.call(req).await
+    }
+
+    fn name(&self) -> &str {
+        self.endpoint.name()
+    }
+
+    fn required_state(&self) -> Vec<StateKey> {
+        self.endpoint.required_state()
+    }
+
+    fn operation(&self) -> Option<&Operation> {
+        Some(&self.operation)
+    }
+}
+
+/// A response type that can be described in an [`Operation`].
+pub trait OperationOutput {
+    /// The content type and schema of the response body, if it has one.
+    fn describe(generator: &mut SchemaGenerator) -> Option<(&'static str, Schema)>;
+}
+
+impl<T: JsonSchema> OperationInput for Json<T> {
+    fn describe(generator: &mut SchemaGenerator, operation: &mut Map<String, Value>) {
+        insert_request_body::<T>(generator, operation, mime::APPLICATION_JSON.as_ref());
+    }
+}
+
+impl<T: JsonSchema> OperationInput for Form<T> {
+    fn describe(generator: &mut SchemaGenerator, operation: &mut Map<String, Value>) {
+        insert_request_body::<T>(
+            generator,
+            operation,
+            mime::APPLICATION_WWW_FORM_URLENCODED.as_ref(),
+        );
+    }
+}
+
+impl<T: JsonSchema> OperationInput for Query<T> {
+    fn describe(generator: &mut SchemaGenerator, operation: &mut Map<String, Value>) {
+        insert_parameters::<T>(generator, operation, "query");
+    }
+}
+
+#[cfg(feature = "nested-query")]
+impl<T: JsonSchema> OperationInput for crate::types::NestedQuery<T> {
+    fn describe(generator: &mut SchemaGenerator, operation: &mut Map<String, Value>) {
+        insert_parameters::<T>(generator, operation, "query");
+    }
+}
+
+impl<T: JsonSchema> OperationInput for Path<T> {
+    fn describe(generator: &mut SchemaGenerator, operation: &mut Map<String, Value>) {
+        insert_parameters::<T>(generator, operation, "path");
+    }
+}
+
+#[cfg(feature = "msgpack")]
+impl<T: JsonSchema> OperationInput for crate::types::MsgPack<T> {
+    fn describe(generator: &mut SchemaGenerator, operation: &mut Map<String, Value>) {
+        insert_request_body::<T>(generator, operation, crate::types::APPLICATION_MSGPACK);
+    }
+}
+
+#[cfg(feature = "cbor")]
+impl<T: JsonSchema> OperationInput for crate::types::Cbor<T> {
+    fn describe(generator: &mut SchemaGenerator, operation: &mut Map<String, Value>) {
+        insert_request_body::<T>(generator, operation, crate::types::APPLICATION_CBOR);
+    }
+}
+
+/// Described with the schema of a single line.
+#[cfg(feature = "ndjson")]
+impl<T: JsonSchema> OperationInput for crate::types::NdJson<crate::types::NdJsonStream<T>> {
+    fn describe(generator: &mut SchemaGenerator, operation: &mut Map<String, Value>) {
+        insert_request_body::<T>(generator, operation, crate::types::APPLICATION_NDJSON);
+    }
+}
+
+impl OperationInput for String {
+    fn describe(generator: &mut SchemaGenerator, operation: &mut Map<String, Value>) {
+        insert_request_body::<String>(generator, operation, mime::TEXT_PLAIN_UTF_8.as_ref());
+    }
+}
+
+impl OperationInput for Bytes {
+    fn describe(_: &mut SchemaGenerator, operation: &mut Map<String, Value>) {
+        insert_binary_body(operation);
+    }
+}
+
+impl OperationInput for BodyStream {
+    fn describe(_: &mut SchemaGenerator, operation: &mut Map<String, Value>) {
+        insert_binary_body(operation);
+    }
+}
+
+#[cfg(feature = "multipart")]
+impl OperationInput for crate::multipart::Multipart {
+    fn describe(_: &mut SchemaGenerator, operation: &mut Map<String, Value>) {
+        let body = operation
+            .entry("requestBody")
+            .or_insert_with(|| json!({ "required": true, "content": {} }));
+        body["content"][mime::MULTIPART_FORM_DATA.as_ref()] =
+            json!({ "schema": { "type": "object" } });
+    }
+}
+
+impl<H: Header> OperationInput for TypedHeader<H> {
+    fn describe(_: &mut SchemaGenerator, operation: &mut Map<String, Value>) {
+        insert_header(operation, H::name().as_str());
+    }
+}
+
+impl OperationInput for LastEventId {
+    fn describe(_: &mut SchemaGenerator, operation: &mut Map<String, Value>) {
+        insert_header(operation, LastEventId::name().as_str());
+    }
+}
+
+/// The inputs of `T`, not required.
+impl<T: OperationInput> OperationInput for Option<T> {
+    fn describe(generator: &mut SchemaGenerator, operation: &mut Map<String, Value>) {
+        describe_optional::<T>(generator, operation);
+    }
+}
+
+/// The inputs of `T`, not required.
+impl<T: OperationInput, E> OperationInput for Result<T, E> {
+    fn describe(generator: &mut SchemaGenerator, operation: &mut Map<String, Value>) {
+        describe_optional::<T>(generator, operation);
+    }
+}
+
+impl<E: OperationInput> OperationInput for Valid<E> {
+    fn describe(generator: &mut SchemaGenerator, operation: &mut Map<String, Value>) {
+        E::describe(generator, operation);
+    }
+}
+
+macro_rules! impl_undocumented_input {
+    ($($(#[$cfg:meta])* $ty:ty),*) => {
+        $(
+            $(#[$cfg])*
+            impl OperationInput for $ty {
+                fn describe(_: &mut SchemaGenerator, _: &mut Map<String, Value>) {}
+            }
+        )*
+    };
+}
+
+impl_undocumented_input!(
+    Request,
+    HeaderMap,
+    Method,
+    Uri,
+    Version,
+    Accept,
+    CookieJar,
+    #[cfg(feature = "cookie-signed")]
+    crate::cookie::SignedCookieJar,
+    #[cfg(feature = "cookie-private")]
+    crate::cookie::PrivateCookieJar
+);
+
+impl<T> OperationInput for AppState<T> {
+    fn describe(_: &mut SchemaGenerator, _: &mut Map<String, Value>) {}
+}
+
+impl<T: JsonSchema> OperationOutput for Json<T> {
+    fn describe(generator: &mut SchemaGenerator) -> Option<(&'static str, Schema)> {
+        Some((
+            mime::APPLICATION_JSON.as_ref(),
+            generator.subschema_for::<T>(),
+        ))
+    }
+}
+
+impl<T: JsonSchema> OperationOutput for Form<T> {
+    fn describe(generator: &mut SchemaGenerator) -> Option<(&'static str, Schema)> {
+        Some((
+            mime::APPLICATION_WWW_FORM_URLENCODED.as_ref(),
+            generator.subschema_for::<T>(),
+        ))
+    }
+}
+
+impl<T> OperationOutput for Html<T> {
+    fn describe(generator: &mut SchemaGenerator) -> Option<(&'static str, Schema)> {
+        Some((
+            mime::TEXT_HTML_UTF_8.as_ref(),
+            generator.subschema_for::<str>(),
+        ))
+    }
+}
+
+impl OperationOutput for String {
+    fn describe(generator: &mut SchemaGenerator) -> Option<(&'static str, Schema)> {
+        Some((
+            mime::TEXT_PLAIN_UTF_8.as_ref(),
+            generator.subschema_for::<str>(),
+        ))
+    }
+}
+
+impl OperationOutput for &'static str {
+    fn describe(generator: &mut SchemaGenerator) -> Option<(&'static str, Schema)> {
+        <String as OperationOutput>::describe(generator)
+    }
+}
+
+impl OperationOutput for Bytes {
+    fn describe(_: &mut SchemaGenerator) -> Option<(&'static str, Schema)> {
+        let schema = json!({ "type": "string", "format": "binary" });
+        Some((
+            mime::APPLICATION_OCTET_STREAM.as_ref(),
+            schema.try_into().expect(crate::GUARANTEE),
+        ))
+    }
+}
+
+impl OperationOutput for () {
+    fn describe(_: &mut SchemaGenerator) -> Option<(&'static str, Schema)> {
+        None
+    }
+}
+
+impl OperationOutput for StatusCode {
+    fn describe(_: &mut SchemaGenerator) -> Option<(&'static str, Schema)> {
+        None
+    }
+}
+
+fn insert_request_body<T: JsonSchema>(
+    generator: &mut SchemaGenerator,
+    operation: &mut Map<String, Value>,
+    content_type: &str,
+) {
+    let schema = generator.subschema_for::<T>();
+    let body = operation
+        .entry("requestBody")
+        .or_insert_with(|| json!({ "required": true, "content": {} }));
+    body["content"][content_type] = json!({ "schema": schema });
+}
+
+fn insert_binary_body(operation: &mut Map<String, Value>) {
+    let body = operation
+        .entry("requestBody")
+        .or_insert_with(|| json!({ "required": true, "content": {} }));
+    body["content"][mime::APPLICATION_OCTET_STREAM.as_ref()] =
+        json!({ "schema": { "type": "string", "format": "binary" } });
+}
+
+fn insert_header(operation: &mut Map<String, Value>, name: &str) {
+    operation
+        .entry("parameters")
+        .or_insert_with(|| Value::Array(vec![]))
+        .as_array_mut()
+        .expect(crate::GUARANTEE)
+        .push(json!({
+            "name": name,
+            "in": "header",
+            "required": true,
+            "schema": { "type": "string" },
+        }));
+}
+
+/// Describe the inputs of `T`, with the parameters and the body it adds not required.
+fn describe_optional<T: OperationInput>(
+    generator: &mut SchemaGenerator,
+    operation: &mut Map<String, Value>,
+) {
+    let mut inner = Map::new();
+    T::describe(generator, &mut inner);
+    if let Some(Value::Array(parameters)) = inner.remove("parameters") {
+        let all = operation
+            .entry("parameters")
+            .or_insert_with(|| Value::Array(vec![]))
+            .as_array_mut()
+            .expect(crate::GUARANTEE);
+        for mut parameter in parameters {
+            // path parameters are always required
+            if parameter["in"] != "path" {
+                parameter["required"] = false.into();
+            }
+            all.push(parameter);
+        }
+    }
+    if let Some(mut body) = inner.remove("requestBody") {
+        body["required"] = false.into();
+        operation.insert("requestBody".into(), body);
+    }
+}
+
+/// Spread the properties of `T`'s object schema into one parameter each.
+fn insert_parameters<T: JsonSchema>(
+    generator: &mut SchemaGenerator,
+    operation: &mut Map<String, Value>,
+    location: &str,
+) {
+    let schema = generator.subschema_for::<T>();
+    let schema = resolve(generator, schema.as_value());
+    let required = schema["required"].as_array().cloned().unwrap_or_default();
+
+    let Some(properties) = schema["properties"].as_object() else {
+        return;
+    };
+    let parameters = operation
+        .entry("parameters")
+        .or_insert_with(|| Value::Array(vec![]))
+        .as_array_mut()
+        .expect(crate::GUARANTEE);
+    for (name, schema) in properties {
+        parameters.push(json!({
+            "name": name,
+            "in": location,
+            "required": location == "path" || required.iter().any(|r| r == name),
+            "schema": schema,
+        }));
+    }
+}
+
+fn resolve(generator: &SchemaGenerator, schema: &Value) -> Value {
+    schema["$ref"]
+        .as_str()
+        .and_then(|r| r.strip_prefix(&format!("#{SCHEMAS_PATH}/")))
+        .and_then(|name| generator.definitions().get(name))
+        .unwrap_or(schema)
+        .clone()
+}
+
+struct PathParam {
+    name: String,
+    schema: Value,
+}
+
+/// Turn a route into an OpenAPI path template: `/user/{id}/{*rest}` becomes `/user/{id}/{rest}`.
+fn path_template(path: &str) -> (String, Vec<String>) {
+    let mut names = vec![];
+    let template = path
+        .split('/')
+        .map(|segment| {
+            if segment.starts_with('{') && !segment.starts_with("{{") && segment.ends_with('}') {
+                let name = segment[1..segment.len() - 1].trim_start_matches('*');
+                names.push(name.to_string());
+                format!("{{{name}}}")
+            } else {
+                segment.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/");
+
+    (template, names)
+}
+
+fn constraint_schema(constraint: Option<&Constraint>) -> Value {
+    match constraint {
+        Some(Constraint::Int) => json!({ "type": "integer", "format": "int64" }),
+        Some(Constraint::Uint) => json!({ "type": "integer", "minimum": 0 }),
+        Some(Constraint::Uuid) => json!({ "type": "string", "format": "uuid" }),
+        Some(Constraint::OneOf(values)) => {
+            json!({ "type": "string", "enum": values.iter().map(|v| &**v).collect::<Vec<_>>() })
+        }
+        Some(Constraint::Regex(re)) => json!({ "type": "string", "pattern": re.as_str() }),
+        None => json!({ "type": "string" }),
+    }
+}
+
+impl Router {
+    /// Generate an OpenAPI 3.1 document for the routes registered so far.
+    ///
+    /// Only method routes are listed; services such as [`Router::serve_dir`] are skipped. Methods
+    /// without an [`Operation`] are listed with an empty default response.
+    pub fn openapi(&self, info: &Info) -> Value {
+        let mut generator = SchemaSettings::draft2020_12()
+            .with(|s| {
+                s.definitions_path = SCHEMAS_PATH.into();
+                s.meta_schema = None;
+            })
+            .into_generator();
+        let mut paths = Map::new();
+
+        for (index, route) in self.routes.iter().enumerate() {
+            let Route::MethodDispatch(dispatch) = route else {
+                continue;
+            };
+            let path = self.index_to_path.get(&index).expect(crate::GUARANTEE);
+            let (template, names) = path_template(path);
+            if names.iter().any(|name| name == NEST_TAIL_PARAM) {
+                continue;
+            }
+
+            let constraints = self.constraints.get(&index);
+            let path_params = names
+                .into_iter()
+                .map(|name| {
+                    let constraint = constraints
+                        .and_then(|cs| cs.iter().find(|(n, _)| **n == *name))
+                        .map(|(_, c)| c);
+                    PathParam {
+                        schema: constraint_schema(constraint),
+                        name,
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            let default = Operation::new();
+            let mut item = Map::new();
+            for (method, layer) in &dispatch.inner {
+                let operation = layer.endpoint.operation().unwrap_or(&default);
+                item.insert(
+                    method.as_str().to_ascii_lowercase(),
+                    operation.to_value(&mut generator, &path_params),
+                );
+            }
+            if !item.is_empty() {
+                paths.insert(template, item.into());
+            }
+        }
+
+        let mut info_value = json!({ "title": info.title, "version": info.version });
+        if let Some(description) = &info.description {
+            info_value["description"] = description.as_str().into();
+        }
+
+        json!({
+            "openapi": OPENAPI_VERSION,
+            "info": info_value,
+            "paths": paths,
+            "components": { "schemas": generator.take_definitions(true) },
+        })
+    }
+
+    /// Serve the OpenAPI document of the routes registered so far as JSON at `path`.
+    ///
+    /// Call this last, routes added afterwards won't be part of the document.
+    pub fn serve_openapi(self, path: &str, info: Info) -> Self {
+        let doc = serde_json::to_vec(&self.openapi(&info)).expect(crate::GUARANTEE);
+        let route =
+            Route::MethodDispatch(Default::default()).register(OpenApiDoc(doc.into()), Method::GET);
+        self.at(path, route)
+    }
+}
+
+struct OpenApiDoc(Bytes);
+
+#[async_trait(?Send)]
+impl Endpoint for OpenApiDoc {
+    async fn call(&self, _req: Request) -> Response {
+        let mut resp = self.0.clone().into_response();
+        resp.headers_mut().insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static(mime::APPLICATION_JSON.as_ref()),
+        );
+        resp
+    }
+}
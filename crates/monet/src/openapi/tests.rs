@@ -0,0 +1,103 @@
+use http::StatusCode;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::json;
+
+use super::{DocumentedHandler, Info, Operation};
+use crate::{Json, Path, Request, Router, get, headers::LastEventId, types::Query};
+
+#[derive(Deserialize, JsonSchema)]
+#[allow(dead_code)]
+struct UserId {
+    id: u32,
+}
+
+#[derive(Deserialize, JsonSchema)]
+#[allow(dead_code)]
+struct Pagination {
+    page: u32,
+    per_page: Option<u32>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+#[allow(dead_code)]
+struct User {
+    name: String,
+}
+
+#[test]
+fn document_from_routes() {
+    let app = Router::new()
+        .at(
+            "/users/{id}",
+            get(show_user.doc(
+                Operation::new()
+                    .summary("Fetch a user")
+                    .tag("users")
+                    .response::<Json<User>>(StatusCode::OK, "The user"),
+            )),
+        )
+        .at(
+            "/users",
+            get(list_users
+                .doc(Operation::new().response::<Json<Vec<User>>>(StatusCode::OK, "All users")))
+            .post(hello),
+        )
+        .at("/users/new", get(create_user.doc(Operation::new())))
+        .at("/posts/{slug:a|b}", get(hello));
+
+    let doc = app.openapi(&Info::new("Users", "1.0.0"));
+
+    assert_eq!(doc["openapi"], "3.1.0");
+    assert_eq!(doc["info"], json!({ "title": "Users", "version": "1.0.0" }));
+
+    let show = &doc["paths"]["/users/{id}"]["get"];
+    assert_eq!(show["summary"], "Fetch a user");
+    assert_eq!(show["tags"], json!(["users"]));
+    assert_eq!(show["parameters"][0]["name"], "id");
+    assert_eq!(show["parameters"][0]["in"], "path");
+    assert_eq!(
+        show["responses"]["200"]["content"]["application/json"]["schema"]["$ref"],
+        "#/components/schemas/User"
+    );
+    assert!(doc["components"]["schemas"]["User"].is_object());
+
+    let list = &doc["paths"]["/users"]["get"]["parameters"];
+    assert_eq!(list[0]["name"], "page");
+    assert_eq!(list[0]["required"], true);
+    assert_eq!(list[1]["name"], "per_page");
+    assert_eq!(list[1]["required"], false);
+    assert_eq!(list[2]["name"], "last-event-id");
+    assert_eq!(list[2]["in"], "header");
+    assert_eq!(list[2]["required"], false);
+    assert!(doc["paths"]["/users"]["post"]["responses"]["default"].is_object());
+
+    let create = &doc["paths"]["/users/new"]["get"]["requestBody"];
+    assert_eq!(create["required"], true);
+    assert_eq!(
+        create["content"]["application/json"]["schema"]["$ref"],
+        "#/components/schemas/User"
+    );
+
+    let posts = &doc["paths"]["/posts/{slug}"]["get"]["parameters"][0];
+    assert_eq!(
+        posts["schema"],
+        json!({ "type": "string", "enum": ["a", "b"] })
+    );
+}
+
+async fn show_user(_id: Path<UserId>) -> &'static str {
+    "user"
+}
+
+async fn list_users(_page: Query<Pagination>, _last: Option<LastEventId>) -> &'static str {
+    "users"
+}
+
+async fn create_user(_user: Json<User>) -> &'static str {
+    "created"
+}
+
+async fn hello(_req: Request) -> &'static str {
+    "hello"
+}
@@ -0,0 +1,199 @@
+//! Machine-readable error bodies, as described by [RFC 9457].
+//!
+//! Errors are rendered as plain text by default. The [`ProblemJson`] middleware renders their
+//! [`ProblemDetails`] as `application/problem+json` instead, either always or when the client
+//! asks for JSON. The details are only built then.
+//!
+//! ```ignore
+//! let app = Router::new()
+//!     .at("/orders", post(create_order))
+//!     .wrap_by(ProblemJson::negotiate());
+//! ```
+//!
+//! [`Error`], [`FieldErrors`] and the types deriving `IntoResponse` provide their details on
+//! their own. Other error types can opt in by inserting a [`ProblemDetails`] into the
+//! extensions of their response, or answer with one directly since it implements
+//! [`IntoResponse`].
+//!
+//! [RFC 9457]: https://www.rfc-editor.org/rfc/rfc9457
+//! [`Error`]: crate::Error
+//! [`FieldErrors`]: crate::error::FieldErrors
+
+#[cfg(test)]
+mod tests;
+
+use async_trait::async_trait;
+use headers::HeaderMapExt;
+use http::{HeaderValue, StatusCode, header};
+use mime::Mime;
+use serde::{Serialize, Serializer};
+use serde_json::{Map, Value};
+
+use crate::{
+    Json, Layer, Middleware, Request,
+    headers::Accept,
+    response::{IntoResponse, Response},
+    router::error_hook::CaughtFailure,
+};
+
+/// The media type of [`ProblemDetails`] rendered as JSON.
+pub const APPLICATION_PROBLEM_JSON: &str = "application/problem+json";
+
+/// A problem details object.
+///
+/// ```ignore
+/// ProblemDetails::new(StatusCode::FORBIDDEN)
+///     .with_type("https://example.com/probs/out-of-credit")
+///     .with_detail("Your current balance is 30, but that costs 50.")
+///     .with_extension("balance", 30)
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ProblemDetails {
+    /// A URI identifying the problem type, `about:blank` when it is only described by the
+    /// status code.
+    #[serde(rename = "type")]
+    pub type_uri: String,
+    pub title: String,
+    #[serde(serialize_with = "serialize_status")]
+    pub status: StatusCode,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    /// A URI identifying this occurrence of the problem. [`ProblemJson`] defaults it to the
+    /// path of the request.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+    /// Additional members, e.g. `field` for the part of the request that was rejected.
+    #[serde(flatten)]
+    pub extensions: Map<String, Value>,
+}
+
+impl ProblemDetails {
+    /// A problem of type `about:blank`, titled after the reason phrase of `status`.
+    pub fn new(status: StatusCode) -> Self {
+        Self {
+            type_uri: "about:blank".to_string(),
+            title: status.canonical_reason().unwrap_or_default().to_string(),
+            status,
+            detail: None,
+            instance: None,
+            extensions: Map::new(),
+        }
+    }
+
+    pub fn with_type(mut self, type_uri: impl Into<String>) -> Self {
+        self.type_uri = type_uri.into();
+        self
+    }
+
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    pub fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    pub fn with_instance(mut self, instance: impl Into<String>) -> Self {
+        self.instance = Some(instance.into());
+        self
+    }
+
+    /// Add a member. Values that fail to serialize are ignored.
+    pub fn with_extension(mut self, key: impl Into<String>, value: impl Serialize) -> Self {
+        if let Ok(value) = serde_json::to_value(value) {
+            self.extensions.insert(key.into(), value);
+        }
+        self
+    }
+}
+
+fn serialize_status<S: Serializer>(status: &StatusCode, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u16(status.as_u16())
+}
+
+impl IntoResponse for ProblemDetails {
+    fn into_response(self) -> Response {
+        let status = self.status;
+        let mut resp = Json(self).into_response();
+        if resp.status().is_success() {
+            *resp.status_mut() = status;
+            resp.headers_mut().insert(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static(APPLICATION_PROBLEM_JSON),
+            );
+        }
+        resp
+    }
+}
+
+/// Render the errors carrying [`ProblemDetails`] as `application/problem+json`.
+///
+/// The headers of the original response, e.g. `Retry-After`, are kept.
+#[derive(Clone, Copy, Debug)]
+pub struct ProblemJson {
+    negotiate: bool,
+}
+
+impl ProblemJson {
+    /// Render every error as problem details.
+    pub fn always() -> Self {
+        Self { negotiate: false }
+    }
+
+    /// Render errors as problem details for clients preferring `application/problem+json` or
+    /// `application/json` to `text/plain` in their `Accept` header, as plain text otherwise.
+    pub fn negotiate() -> Self {
+        Self { negotiate: true }
+    }
+
+    fn wants_problem(&self, req: &Request) -> bool {
+        if !self.negotiate {
+            return true;
+        }
+        let Some(accept) = req.headers().typed_get::<Accept>() else {
+            return false;
+        };
+        let available: [Mime; 3] = [
+            mime::TEXT_PLAIN,
+            APPLICATION_PROBLEM_JSON.parse().expect(crate::GUARANTEE),
+            mime::APPLICATION_JSON,
+        ];
+        accept
+            .preferred(&available)
+            .is_some_and(|mime| *mime != mime::TEXT_PLAIN)
+    }
+}
+
+#[async_trait(?Send)]
+impl Middleware for ProblemJson {
+    async fn transform(&self, req: Request, layer: Layer) -> Response {
+        let wants_problem = self.wants_problem(&req);
+        let path = req.uri().path().to_string();
+        let mut resp = layer.next(req).await;
+        if !wants_problem {
+            return resp;
+        }
+        let problem = resp
+            .extensions_mut()
+            .remove::<ProblemDetails>()
+            .or_else(|| {
+                let CaughtFailure(failure) = resp.extensions().get::<CaughtFailure>()?;
+                failure.problem()
+            });
+        let Some(mut problem) = problem else {
+            return resp;
+        };
+        problem.instance.get_or_insert(path);
+
+        let mut problem_resp = problem.into_response();
+        *problem_resp.extensions_mut() = std::mem::take(resp.extensions_mut());
+        for (name, value) in resp.headers() {
+            if name != header::CONTENT_TYPE && name != header::CONTENT_LENGTH {
+                problem_resp.headers_mut().append(name, value.clone());
+            }
+        }
+        problem_resp
+    }
+}
@@ -0,0 +1,129 @@
+use http::{HeaderValue, Method, StatusCode, header};
+use http_body_util::{BodyExt, Full};
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use super::{APPLICATION_PROBLEM_JSON, ProblemDetails, ProblemJson};
+use crate::{Json, Router, body::Body, get, post, router::tests::request};
+
+#[derive(Debug, Deserialize)]
+#[allow(dead_code)]
+struct NewUser {
+    name: String,
+    age: u8,
+}
+
+#[derive(Debug, thiserror::Error, crate::IntoResponse)]
+#[error("the shop is closed")]
+#[status(503)]
+#[header("retry-after", "3600")]
+struct Closed;
+
+fn app() -> Router {
+    async fn create(Json(_): Json<NewUser>) {}
+    async fn closed() -> Result<(), Closed> {
+        Err(Closed)
+    }
+
+    Router::new()
+        .at("/users", post(create))
+        .at("/shop", get(closed))
+        .wrap_by(ProblemJson::negotiate())
+}
+
+async fn send(
+    app: &Router,
+    method: Method,
+    uri: &str,
+    accept: Option<&'static str>,
+) -> crate::Response {
+    let mut req = request(method, uri);
+    req.headers_mut().insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/json"),
+    );
+    if let Some(accept) = accept {
+        req.headers_mut()
+            .insert(header::ACCEPT, HeaderValue::from_static(accept));
+    }
+    req.body = Body::new(Full::new(r#"{"name": "ann", "age": 300}"#.into()));
+    app.handle(req).await
+}
+
+async fn json_body(resp: crate::Response) -> Value {
+    let body = resp.into_body().collect().await.unwrap().to_bytes();
+    serde_json::from_slice(&body).unwrap()
+}
+
+#[compio::test]
+async fn negotiated_problem_json() {
+    let app = app();
+
+    let resp = send(&app, Method::POST, "/users", Some("application/json")).await;
+    assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(
+        resp.headers()[header::CONTENT_TYPE],
+        APPLICATION_PROBLEM_JSON
+    );
+    let body = json_body(resp).await;
+    assert_eq!(body["type"], "about:blank");
+    assert_eq!(body["title"], "Unprocessable Entity");
+    assert_eq!(body["status"], 422);
+    assert_eq!(body["instance"], "/users");
+    assert_eq!(body["field"], "age");
+
+    let resp = send(&app, Method::POST, "/users", Some("text/html, */*;q=0.8")).await;
+    assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    // Nothing to build when the client doesn't want it.
+    assert!(resp.extensions().get::<ProblemDetails>().is_none());
+    assert_ne!(
+        resp.headers()
+            .get(header::CONTENT_TYPE)
+            .map(|v| v.as_bytes()),
+        Some(APPLICATION_PROBLEM_JSON.as_bytes())
+    );
+
+    let resp = send(&app, Method::GET, "/shop", Some("application/problem+json")).await;
+    assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(resp.headers()[header::RETRY_AFTER], "3600");
+    assert_eq!(
+        json_body(resp).await,
+        json!({
+            "type": "about:blank",
+            "title": "Service Unavailable",
+            "status": 503,
+            "detail": "the shop is closed",
+            "instance": "/shop",
+        })
+    );
+}
+
+#[compio::test]
+async fn always_problem_json() {
+    let app = Router::new()
+        .at("/users", post(async |Json(_): Json<NewUser>| ()))
+        .wrap_by(ProblemJson::always());
+
+    let resp = send(&app, Method::POST, "/users", None).await;
+    assert_eq!(
+        resp.headers()[header::CONTENT_TYPE],
+        APPLICATION_PROBLEM_JSON
+    );
+}
+
+#[test]
+fn custom_problem() {
+    let problem = ProblemDetails::new(StatusCode::FORBIDDEN)
+        .with_type("https://example.com/probs/out-of-credit")
+        .with_title("You do not have enough credit.")
+        .with_extension("balance", 30);
+    assert_eq!(
+        serde_json::to_value(&problem).unwrap(),
+        json!({
+            "type": "https://example.com/probs/out-of-credit",
+            "title": "You do not have enough credit.",
+            "status": 403,
+            "balance": 30,
+        })
+    );
+}
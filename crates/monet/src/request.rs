@@ -1,27 +1,34 @@
+mod de;
+
 use std::{
     any::{Any, TypeId},
     collections::HashMap,
+    fmt,
     hash::{BuildHasherDefault, Hasher},
+    rc::Rc,
     sync::Arc,
 };
 
 use bytes::Bytes;
-use http::{Extensions, HeaderMap, Method, Uri, Version, request::Parts};
-use http_body_util::BodyExt;
+use http::{Extensions, HeaderMap, Method, Uri, Version, header, request::Parts};
+use http_body_util::{BodyExt, Limited};
 use hyper::body::Incoming as IncomingBody;
 use serde_core::de::DeserializeOwned;
 
+use self::de::PathDeserializer;
 use crate::{
-    body::Body,
+    body::{Body, BodyReader, BodyStream},
     error::Error,
+    handler::middleware::body_limit::{BodyLimit, DEFAULT_BODY_LIMIT},
     router::url::UrlParams,
-    types::{Form, Json, Path, Query, has_content_type},
+    types::{ContentTypes, Form, FormConfig, Json, JsonConfig, Path, Query},
 };
 
 pub struct Request {
     pub body: Body,
     pub head: Parts,
     pub state: State,
+    pub(crate) app_state: AppStateMap,
 }
 
 impl Request {
@@ -75,28 +82,29 @@ impl Request {
         &mut self.head.extensions
     }
 
+    /// Borrow the application state of type `T` provided with [`Router::with_state`].
+    ///
+    /// [`Router::with_state`]: crate::Router::with_state
+    pub fn app_state<T: 'static>(&self) -> Result<&T, Error> {
+        self.app_state
+            .get()
+            .ok_or(Error::MissingAppState(std::any::type_name::<T>()))
+    }
+
+    /// Deserialize the parameters of the matched route, see [`Path`].
+    ///
+    /// Values that don't parse are rejected with `400 Bad Request`, types that don't fit the
+    /// route with `500 Internal Server Error`, as described by [`PathError`].
+    ///
+    /// [`PathError`]: crate::error::PathError
     pub fn path<T>(&self) -> Result<Path<T>, Error>
     where
         T: DeserializeOwned,
     {
-        /*
-         * Given route `/user/{id}/{*name}` and request `/user/23/david`, the data flow would be:
-         * Vec[("id", "23"), ("name", "david")] -> id=23&name=david -> Path(T {id: 23, name: david})
-         */
         match self.extensions().get::<UrlParams>() {
-            Some(UrlParams::Params(params)) => {
-                let mut serializer = form_urlencoded::Serializer::new(String::new());
-                params.iter().for_each(|(k, v)| {
-                    serializer.append_pair(k, v);
-                });
-                let encoded_querystring = serializer.finish();
-
-                let parser = form_urlencoded::parse(encoded_querystring.as_bytes());
-                let deserializer = serde_urlencoded::Deserializer::new(parser);
-                serde_path_to_error::deserialize(deserializer)
-                    .map(Path)
-                    .map_err(Error::FailedToDeserializePathParams)
-            }
+            Some(UrlParams::Params(params)) => T::deserialize(PathDeserializer::new(params))
+                .map(Path)
+                .map_err(Error::FailedToDeserializePathParams),
             Some(UrlParams::InvalidUtf8InPathParam { key }) => Err(Error::InvalidUtf8InPathParam {
                 key: key.to_string(),
             }),
@@ -110,12 +118,34 @@ impl Request {
     {
         let query = self.uri().query().unwrap_or_default();
         let parser = form_urlencoded::parse(query.as_bytes());
-        let deserializer = serde_urlencoded::Deserializer::new(parser);
+        let deserializer = serde_html_form::Deserializer::new(parser);
         serde_path_to_error::deserialize(deserializer)
             .map(Query)
             .map_err(Error::FailedToDeserializeQuery)
     }
 
+    /// Deserialize the query string with bracket-style nesting, see [`NestedQuery`].
+    ///
+    /// [`NestedQuery`]: crate::types::NestedQuery
+    #[cfg(feature = "nested-query")]
+    pub fn nested_query<T>(&self) -> Result<crate::types::NestedQuery<T>, Error>
+    where
+        T: DeserializeOwned,
+    {
+        // Not strict, so brackets percent-encoded by browsers are accepted too.
+        let config = serde_qs::Config::new(5, false);
+        let query = self.uri().query().unwrap_or_default();
+        let deserializer =
+            serde_qs::Deserializer::with_config(&config, query.as_bytes()).map_err(|err| {
+                // The query string itself is malformed, no field is involved
+                let path = serde_path_to_error::Track::new().path();
+                Error::FailedToDeserializeNestedQuery(serde_path_to_error::Error::new(path, err))
+            })?;
+        serde_path_to_error::deserialize(deserializer)
+            .map(crate::types::NestedQuery)
+            .map_err(Error::FailedToDeserializeNestedQuery)
+    }
+
     // #[cfg(not(feature = "no-matched-path"))]
     pub fn matched_path(&self) -> Option<&Arc<str>> {
         use crate::router::url::MatchedPath;
@@ -138,7 +168,7 @@ impl Request {
                 Bytes::new()
             }
         } else {
-            if has_content_type(self.headers(), &mime::APPLICATION_WWW_FORM_URLENCODED) {
+            if self.accepts_content_type(|config: &FormConfig| &config.content_types) {
                 self.into_bytes().await?
             } else {
                 return Err(Error::InvalidFormContentType);
@@ -149,15 +179,95 @@ impl Request {
         serde_path_to_error::deserialize(deserializer).map_err(Error::FailedToDeserializeForm)
     }
 
+    /// The maximum size of the body, `None` if unlimited. See [`DefaultBodyLimit`].
+    ///
+    /// [`DefaultBodyLimit`]: crate::handler::middleware::body_limit::DefaultBodyLimit
+    pub fn body_limit(&self) -> Option<usize> {
+        self.extensions()
+            .get::<BodyLimit>()
+            .map_or(Some(DEFAULT_BODY_LIMIT), |limit| limit.0)
+    }
+
+    /// The body, failing with [`Error::PayloadTooLarge`] if the `Content-Length` is over
+    /// [`Request::body_limit`], and with a [`BodyError`] whose
+    /// [`is_length_limit`](crate::BodyError::is_length_limit) is set once more than the limit is
+    /// read.
+    ///
+    /// [`BodyError`]: crate::BodyError
+    pub fn into_limited_body(self) -> Result<Body, Error> {
+        let Some(limit) = self.body_limit() else {
+            return Ok(self.body);
+        };
+        let content_length = self
+            .headers()
+            .get(header::CONTENT_LENGTH)
+            .and_then(|len| len.to_str().ok())
+            .and_then(|len| len.parse::<u64>().ok());
+        if content_length.is_some_and(|len| len > limit as u64) {
+            return Err(Error::PayloadTooLarge { limit });
+        }
+        Ok(Body::new(Limited::new(self.body, limit)))
+    }
+
+    /// Read the body chunk by chunk as it arrives, instead of buffering it.
+    ///
+    /// The body limit still applies, a larger body ends the stream with
+    /// [`Error::PayloadTooLarge`]. Raise it with [`DefaultBodyLimit`] on routes taking large
+    /// uploads.
+    ///
+    /// ```ignore
+    /// async fn upload(req: Request) -> Result<String, Error> {
+    ///     let mut stream = req.into_stream();
+    ///     let mut size = 0;
+    ///     while let Some(chunk) = stream.next().await {
+    ///         size += chunk?.len();
+    ///     }
+    ///     Ok(format!("{size} bytes, trailers: {:?}", stream.trailers()))
+    /// }
+    /// ```
+    ///
+    /// [`DefaultBodyLimit`]: crate::DefaultBodyLimit
+    pub fn into_stream(self) -> BodyStream {
+        let limit = self.body_limit();
+        BodyStream::new(self.into_limited_body(), limit)
+    }
+
+    /// Read the body through the compio `AsyncRead` trait, see [`BodyReader`].
+    pub fn into_reader(self) -> BodyReader {
+        self.into_stream().into_reader()
+    }
+
+    /// Buffer the whole body, up to [`Request::body_limit`] bytes.
     pub async fn into_bytes(self) -> Result<Bytes, Error> {
-        Ok(self.body.collect().await?.to_bytes())
+        let limit = self.body_limit().unwrap_or(usize::MAX);
+        match self.into_limited_body()?.collect().await {
+            Ok(collected) => Ok(collected.to_bytes()),
+            Err(err) if err.is_length_limit() => Err(Error::PayloadTooLarge { limit }),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Whether the `Content-Type` is accepted by the config `C` of the innermost route
+    /// setting one, the one provided as application state, or else the default one.
+    fn accepts_content_type<C>(&self, content_types: impl Fn(&C) -> &ContentTypes) -> bool
+    where
+        C: Default + Send + Sync + 'static,
+    {
+        let config = self
+            .extensions()
+            .get::<C>()
+            .or_else(|| self.app_state.get::<C>());
+        match config {
+            Some(config) => content_types(config).matches_headers(self.headers()),
+            None => content_types(&C::default()).matches_headers(self.headers()),
+        }
     }
 
     pub async fn into_json<T>(self) -> Result<Json<T>, Error>
     where
         T: DeserializeOwned,
     {
-        if !has_content_type(self.headers(), &mime::APPLICATION_JSON) {
+        if !self.accepts_content_type(|config: &JsonConfig| &config.content_types) {
             return Err(Error::InvalidJsonContentType);
         }
 
@@ -171,6 +281,57 @@ impl Request {
             Err(err) => Err(Error::JsonDataError(err)),
         }
     }
+
+    #[cfg(feature = "msgpack")]
+    pub async fn into_msgpack<T>(self) -> Result<crate::types::MsgPack<T>, Error>
+    where
+        T: DeserializeOwned,
+    {
+        if !self.accepts_content_type(|config: &crate::types::MsgPackConfig| &config.content_types)
+        {
+            return Err(Error::InvalidMsgPackContentType);
+        }
+        let bytes = self.into_bytes().await?;
+        let mut deserializer = rmp_serde::Deserializer::from_read_ref(&bytes);
+        serde_path_to_error::deserialize(&mut deserializer)
+            .map(crate::types::MsgPack)
+            .map_err(Error::MsgPackError)
+    }
+
+    #[cfg(feature = "cbor")]
+    pub async fn into_cbor<T>(self) -> Result<crate::types::Cbor<T>, Error>
+    where
+        T: DeserializeOwned,
+    {
+        if !self.accepts_content_type(|config: &crate::types::CborConfig| &config.content_types) {
+            return Err(Error::InvalidCborContentType);
+        }
+        let bytes = self.into_bytes().await?;
+        ciborium::from_reader(&bytes[..])
+            .map(crate::types::Cbor)
+            .map_err(Error::CborError)
+    }
+
+    /// Parse the body as newline-delimited JSON while it arrives, see [`NdJsonStream`].
+    ///
+    /// The body limit still applies to the whole body, use [`DefaultBodyLimit::disable`] for
+    /// long-lived streams.
+    ///
+    /// [`NdJsonStream`]: crate::types::NdJsonStream
+    /// [`DefaultBodyLimit::disable`]: crate::DefaultBodyLimit::disable
+    #[cfg(feature = "ndjson")]
+    pub fn into_ndjson<T>(
+        self,
+    ) -> Result<crate::types::NdJson<crate::types::NdJsonStream<T>>, Error> {
+        if !self.accepts_content_type(|config: &crate::types::NdJsonConfig| &config.content_types) {
+            return Err(Error::InvalidNdJsonContentType);
+        }
+        let limit = self.body_limit();
+        let body = self.into_limited_body()?;
+        Ok(crate::types::NdJson(crate::types::NdJsonStream::new(
+            body, limit,
+        )))
+    }
 }
 
 impl From<http::Request<IncomingBody>> for Request {
@@ -181,6 +342,7 @@ impl From<http::Request<IncomingBody>> for Request {
             head: parts,
             body: Body::new(body),
             state: State { inner: None },
+            app_state: AppStateMap::default(),
         }
     }
 }
@@ -239,6 +401,93 @@ impl State {
     }
 }
 
+/// Router-wide application state, one value per type.
+///
+/// Unlike [`State`], values don't need to be `Clone`: the map is shared by every request
+/// handled by the router, so handing it to a request only bumps a reference count.
+#[derive(Clone, Default)]
+pub struct AppStateMap {
+    inner: Rc<HashMap<TypeId, Rc<dyn Any>, BuildHasherDefault<IdHasher>>>,
+}
+
+impl AppStateMap {
+    pub fn get<T: 'static>(&self) -> Option<&T> {
+        self.inner
+            .get(&TypeId::of::<T>())
+            .and_then(|val| val.downcast_ref())
+    }
+
+    pub fn get_rc<T: 'static>(&self) -> Option<Rc<T>> {
+        self.inner
+            .get(&TypeId::of::<T>())
+            .and_then(|val| val.clone().downcast().ok())
+    }
+
+    pub fn contains(&self, key: &StateKey) -> bool {
+        self.inner.contains_key(&key.id)
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    pub(crate) fn insert<T: 'static>(&mut self, val: T) {
+        self.insert_rc(Rc::new(val));
+    }
+
+    pub(crate) fn insert_rc<T: 'static>(&mut self, val: Rc<T>) {
+        Rc::make_mut(&mut self.inner).insert(TypeId::of::<T>(), val);
+    }
+
+    /// Add the values of `other` whose type is not in `self` yet.
+    pub(crate) fn merge(&mut self, other: &Self) {
+        if other.is_empty() {
+            return;
+        }
+        let inner = Rc::make_mut(&mut self.inner);
+        other.inner.iter().for_each(|(id, val)| {
+            inner.entry(*id).or_insert_with(|| val.clone());
+        });
+    }
+}
+
+impl fmt::Debug for AppStateMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AppStateMap")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+/// Identifies an application state type an [`Endpoint`] or [`Middleware`] depends on.
+///
+/// [`Endpoint`]: crate::Endpoint
+/// [`Middleware`]: crate::Middleware
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StateKey {
+    id: TypeId,
+    name: &'static str,
+}
+
+impl StateKey {
+    pub fn of<T: 'static>() -> Self {
+        Self {
+            id: TypeId::of::<T>(),
+            name: std::any::type_name::<T>(),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
 #[derive(Default)]
 struct IdHasher(u64);
 
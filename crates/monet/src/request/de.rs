@@ -0,0 +1,423 @@
+//! Deserialization of the matched path parameters into the type of a `Path<T>`.
+//!
+//! A single parameter can be deserialized into a plain value (`Path<u32>`), several into a tuple
+//! or a sequence (`Path<(String, u32)>`), and named ones into a struct or a map.
+
+use std::{any::type_name, fmt::Display, sync::Arc};
+
+use serde::de::{
+    self, DeserializeSeed, EnumAccess, MapAccess, SeqAccess, VariantAccess, Visitor,
+    value::BorrowedStrDeserializer,
+};
+
+use crate::error::PathError;
+
+impl de::Error for PathError {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self::Message(msg.to_string())
+    }
+
+    fn missing_field(field: &'static str) -> Self {
+        Self::MissingParam(field.to_string())
+    }
+}
+
+pub(crate) struct PathDeserializer<'de> {
+    params: &'de [(Arc<str>, Arc<str>)],
+}
+
+impl<'de> PathDeserializer<'de> {
+    pub(crate) fn new(params: &'de [(Arc<str>, Arc<str>)]) -> Self {
+        Self { params }
+    }
+
+    /// The only parameter, for types made of a single value.
+    fn single(&self) -> Result<ValueDeserializer<'de>, PathError> {
+        match self.params {
+            [(key, value)] => Ok(ValueDeserializer { key, value }),
+            _ => Err(PathError::WrongNumberOfParams {
+                expected: 1,
+                got: self.params.len(),
+            }),
+        }
+    }
+}
+
+macro_rules! forward_to_single {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                let value = self.single()?;
+                value.$method(visitor).map_err(|err| value.blame(err))
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for PathDeserializer<'de> {
+    type Error = PathError;
+
+    forward_to_single! {
+        deserialize_bool deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64
+        deserialize_i128 deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64
+        deserialize_u128 deserialize_f32 deserialize_f64 deserialize_char deserialize_str
+        deserialize_string deserialize_bytes deserialize_byte_buf deserialize_option
+        deserialize_identifier
+    }
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_seq(ParamsSeq {
+            params: self.params.iter(),
+        })
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        if self.params.len() != len {
+            return Err(PathError::WrongNumberOfParams {
+                expected: len,
+                got: self.params.len(),
+            });
+        }
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_map(ParamsMap {
+            params: self.params.iter(),
+            value: None,
+        })
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        let value = self.single()?;
+        value
+            .deserialize_enum(name, variants, visitor)
+            .map_err(|err| value.blame(err))
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+}
+
+struct ParamsSeq<'de> {
+    params: std::slice::Iter<'de, (Arc<str>, Arc<str>)>,
+}
+
+impl<'de> SeqAccess<'de> for ParamsSeq<'de> {
+    type Error = PathError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Self::Error> {
+        self.params
+            .next()
+            .map(|(key, value)| ValueDeserializer { key, value }.seed(seed))
+            .transpose()
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.params.len())
+    }
+}
+
+struct ParamsMap<'de> {
+    params: std::slice::Iter<'de, (Arc<str>, Arc<str>)>,
+    value: Option<ValueDeserializer<'de>>,
+}
+
+impl<'de> MapAccess<'de> for ParamsMap<'de> {
+    type Error = PathError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        let Some((key, value)) = self.params.next() else {
+            return Ok(None);
+        };
+        self.value = Some(ValueDeserializer { key, value });
+        seed.deserialize(BorrowedStrDeserializer::new(key))
+            .map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, Self::Error> {
+        match self.value.take() {
+            Some(value) => value.seed(seed),
+            None => Err(PathError::Message(
+                "value requested before its key".to_string(),
+            )),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.params.len())
+    }
+}
+
+/// The value of a single parameter.
+#[derive(Clone, Copy)]
+struct ValueDeserializer<'de> {
+    key: &'de str,
+    value: &'de str,
+}
+
+impl<'de> ValueDeserializer<'de> {
+    /// Deserialize the value with `seed`, see [`Self::blame`].
+    fn seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, PathError> {
+        seed.deserialize(self).map_err(|err| self.blame(err))
+    }
+
+    /// Attribute the errors of the value itself, e.g. an unknown enum variant or a failed
+    /// `#[serde(try_from)]`, to the parameter.
+    fn blame(&self, err: PathError) -> PathError {
+        match err {
+            PathError::Message(message) => PathError::InvalidParam {
+                name: self.key.to_string(),
+                value: self.value.to_string(),
+                message,
+            },
+            err => err,
+        }
+    }
+
+    fn parse_error(&self, expected: &'static str) -> PathError {
+        PathError::ParseParam {
+            name: self.key.to_string(),
+            value: self.value.to_string(),
+            expected,
+        }
+    }
+}
+
+macro_rules! parse_value {
+    ($($method:ident => $visit:ident($ty:ty),)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                let value = self
+                    .value
+                    .parse::<$ty>()
+                    .map_err(|_| self.parse_error(stringify!($ty)))?;
+                visitor.$visit(value)
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for ValueDeserializer<'de> {
+    type Error = PathError;
+
+    parse_value! {
+        deserialize_bool => visit_bool(bool),
+        deserialize_i8 => visit_i8(i8),
+        deserialize_i16 => visit_i16(i16),
+        deserialize_i32 => visit_i32(i32),
+        deserialize_i64 => visit_i64(i64),
+        deserialize_i128 => visit_i128(i128),
+        deserialize_u8 => visit_u8(u8),
+        deserialize_u16 => visit_u16(u16),
+        deserialize_u32 => visit_u32(u32),
+        deserialize_u64 => visit_u64(u64),
+        deserialize_u128 => visit_u128(u128),
+        deserialize_f32 => visit_f32(f32),
+        deserialize_f64 => visit_f64(f64),
+        deserialize_char => visit_char(char),
+    }
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_borrowed_str(self.value)
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_borrowed_str(self.value)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_borrowed_str(self.value)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_borrowed_bytes(self.value.as_bytes())
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_borrowed_bytes(self.value.as_bytes())
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_borrowed_str(self.value)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_enum(self)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+        Err(PathError::UnsupportedType(type_name::<V::Value>()))
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        Err(PathError::UnsupportedType(type_name::<V::Value>()))
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        Err(PathError::UnsupportedType(type_name::<V::Value>()))
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+        Err(PathError::UnsupportedType(type_name::<V::Value>()))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        Err(PathError::UnsupportedType(type_name::<V::Value>()))
+    }
+}
+
+impl<'de> EnumAccess<'de> for ValueDeserializer<'de> {
+    type Error = PathError;
+    type Variant = UnitVariant;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self::Variant), Self::Error> {
+        let variant = seed.deserialize(BorrowedStrDeserializer::new(self.value))?;
+        Ok((variant, UnitVariant))
+    }
+}
+
+/// Only unit variants can be named by a parameter.
+struct UnitVariant;
+
+impl<'de> VariantAccess<'de> for UnitVariant {
+    type Error = PathError;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        _seed: T,
+    ) -> Result<T::Value, Self::Error> {
+        Err(PathError::UnsupportedType("newtype enum variant"))
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        Err(PathError::UnsupportedType("tuple enum variant"))
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        Err(PathError::UnsupportedType("struct enum variant"))
+    }
+}
@@ -1,3 +1,9 @@
+mod negotiate;
+mod sse;
+mod stream;
+#[cfg(test)]
+mod tests;
+
 use std::borrow::Cow;
 
 use bytes::{BufMut, Bytes, BytesMut};
@@ -6,6 +12,12 @@ use serde::Serialize;
 
 use crate::{BoxError, Form, Json, body::Body, types::Html};
 
+pub use self::{
+    negotiate::Negotiate,
+    sse::{Event, KeepAlive, Sse},
+    stream::StreamBody,
+};
+
 pub type Response<T = Body> = HttpResponse<T>;
 
 pub trait IntoResponse {
@@ -82,6 +94,12 @@ impl IntoResponse for StatusCode {
     }
 }
 
+impl IntoResponse for std::convert::Infallible {
+    fn into_response(self) -> Response {
+        match self {}
+    }
+}
+
 impl IntoResponse for () {
     fn into_response(self) -> Response {
         Response::new(Body::empty())
@@ -200,6 +218,74 @@ where
     }
 }
 
+#[cfg(feature = "msgpack")]
+impl<T> IntoResponse for crate::types::MsgPack<T>
+where
+    T: Serialize,
+{
+    fn into_response(self) -> Response {
+        match rmp_serde::to_vec_named(&self.0) {
+            Ok(buf) => {
+                let mut resp = buf.into_response();
+                resp.headers_mut().insert(
+                    CONTENT_TYPE,
+                    HeaderValue::from_static(crate::types::APPLICATION_MSGPACK),
+                );
+                resp
+            }
+            Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response(),
+        }
+    }
+}
+
+#[cfg(feature = "cbor")]
+impl<T> IntoResponse for crate::types::Cbor<T>
+where
+    T: Serialize,
+{
+    fn into_response(self) -> Response {
+        let mut buf = Vec::with_capacity(128);
+        match ciborium::into_writer(&self.0, &mut buf) {
+            Ok(()) => {
+                let mut resp = buf.into_response();
+                resp.headers_mut().insert(
+                    CONTENT_TYPE,
+                    HeaderValue::from_static(crate::types::APPLICATION_CBOR),
+                );
+                resp
+            }
+            Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response(),
+        }
+    }
+}
+
+/// Each value is serialized on its own line as soon as the stream yields it. A value that
+/// fails to serialize aborts the response.
+#[cfg(feature = "ndjson")]
+impl<S, T> IntoResponse for crate::types::NdJson<S>
+where
+    S: futures::Stream<Item = T> + 'static,
+    T: Serialize,
+{
+    fn into_response(self) -> Response {
+        use futures::StreamExt;
+
+        let frames = self.0.map(|value| {
+            let mut buf = BytesMut::with_capacity(128).writer();
+            serde_json::to_writer(&mut buf, &value)?;
+            let mut buf = buf.into_inner();
+            buf.put_u8(b'\n');
+            Ok::<_, serde_json::Error>(http_body::Frame::data(buf.freeze()))
+        });
+        let mut resp = Response::new(Body::from_frames(frames));
+        resp.headers_mut().insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static(crate::types::APPLICATION_NDJSON),
+        );
+        resp
+    }
+}
+
 impl<T> IntoResponse for Form<T>
 where
     T: Serialize,
@@ -240,3 +326,34 @@ where
         resp
     }
 }
+
+/// Helpers for the code generated by `#[derive(IntoResponse)]`.
+#[doc(hidden)]
+pub mod __private {
+    use http::{HeaderName, HeaderValue, StatusCode};
+
+    use super::{IntoResponse, Response};
+    use crate::{Json, problem::ProblemDetails};
+
+    pub fn error_response(
+        status: StatusCode,
+        message: String,
+        json: bool,
+        headers: &[(&'static str, &'static str)],
+    ) -> Response {
+        let problem = ProblemDetails::new(status).with_detail(&message);
+        let mut resp = match json {
+            true => Json(serde_json::json!({ "error": message })).into_response(),
+            false => message.into_response(),
+        };
+        *resp.status_mut() = status;
+        resp.extensions_mut().insert(problem);
+        for (name, value) in headers {
+            resp.headers_mut().insert(
+                HeaderName::from_static(name),
+                HeaderValue::from_static(value),
+            );
+        }
+        resp
+    }
+}
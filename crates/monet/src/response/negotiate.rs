@@ -0,0 +1,118 @@
+use std::fmt;
+
+use http::{HeaderValue, StatusCode, header};
+use mime::Mime;
+use serde::Serialize;
+
+use crate::{
+    Form, Json,
+    headers::Accept,
+    response::{IntoResponse, Response},
+    types::Html,
+};
+
+type Render<T> = Box<dyn FnOnce(&T) -> String>;
+
+/// A value rendered in the representation the client prefers according to its `Accept`
+/// header, or `406 Not Acceptable` when it accepts none of them.
+///
+/// The value is always available as JSON, as a form and, with the `msgpack` feature, as
+/// MessagePack. HTML and plain text are offered once a renderer is given. On a tie, they are
+/// preferred in this order: JSON, MessagePack, form, HTML, text.
+///
+/// ```ignore
+/// async fn show_user(accept: Accept, Path(id): Path<u32>) -> Negotiate<User> {
+///     Negotiate::new(accept, find_user(id))
+///         .html(|user| templates::user_page(user))
+///         .text(|user| user.name.clone())
+/// }
+/// ```
+#[must_use]
+pub struct Negotiate<T> {
+    value: T,
+    accept: Accept,
+    html: Option<Render<T>>,
+    text: Option<Render<T>>,
+}
+
+impl<T> Negotiate<T> {
+    pub fn new(accept: Accept, value: T) -> Self {
+        Self {
+            value,
+            accept,
+            html: None,
+            text: None,
+        }
+    }
+
+    /// Offer `text/html`, rendered by `render`, e.g. with a template.
+    pub fn html(mut self, render: impl FnOnce(&T) -> String + 'static) -> Self {
+        self.html = Some(Box::new(render));
+        self
+    }
+
+    /// Offer `text/plain`, rendered by `render`.
+    pub fn text(mut self, render: impl FnOnce(&T) -> String + 'static) -> Self {
+        self.text = Some(Box::new(render));
+        self
+    }
+
+    fn available(&self) -> Vec<Mime> {
+        let mut available = vec![mime::APPLICATION_JSON];
+        #[cfg(feature = "msgpack")]
+        available.push(
+            crate::types::APPLICATION_MSGPACK
+                .parse()
+                .expect(crate::GUARANTEE),
+        );
+        available.push(mime::APPLICATION_WWW_FORM_URLENCODED);
+        if self.html.is_some() {
+            available.push(mime::TEXT_HTML);
+        }
+        if self.text.is_some() {
+            available.push(mime::TEXT_PLAIN);
+        }
+        available
+    }
+}
+
+impl<T: Serialize> IntoResponse for Negotiate<T> {
+    fn into_response(self) -> Response {
+        let available = self.available();
+        let mut resp = match self.accept.preferred(&available) {
+            Some(mime) if *mime == mime::APPLICATION_JSON => Json(self.value).into_response(),
+            #[cfg(feature = "msgpack")]
+            Some(mime) if mime.as_ref() == crate::types::APPLICATION_MSGPACK => {
+                crate::types::MsgPack(self.value).into_response()
+            }
+            Some(mime) if *mime == mime::APPLICATION_WWW_FORM_URLENCODED => {
+                Form(self.value).into_response()
+            }
+            Some(mime) if *mime == mime::TEXT_HTML => {
+                let render = self.html.expect(crate::GUARANTEE);
+                Html(render(&self.value)).into_response()
+            }
+            Some(_) => {
+                let render = self.text.expect(crate::GUARANTEE);
+                render(&self.value).into_response()
+            }
+            None => {
+                let available = available.iter().map(Mime::as_ref).collect::<Vec<_>>();
+                let message = format!("Available representations: {}", available.join(", "));
+                (StatusCode::NOT_ACCEPTABLE, message).into_response()
+            }
+        };
+        resp.headers_mut()
+            .append(header::VARY, HeaderValue::from_static("accept"));
+        resp
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Negotiate<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Negotiate")
+            .field("value", &self.value)
+            .field("accept", &self.accept)
+            .finish_non_exhaustive()
+    }
+}
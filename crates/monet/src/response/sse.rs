@@ -0,0 +1,223 @@
+use std::{fmt, time::Duration};
+
+use bytes::{BufMut, Bytes, BytesMut};
+use futures::{Stream, StreamExt};
+use http::{HeaderValue, header};
+use serde::Serialize;
+
+use crate::{
+    BoxError,
+    body::Body,
+    response::{IntoResponse, Response},
+};
+
+/// A `text/event-stream` response pushing [`Event`]s to the client as the stream yields them,
+/// e.g. to an `EventSource` in a browser.
+///
+/// ```ignore
+/// async fn updates(last_id: Option<LastEventId>) -> impl IntoResponse {
+///     let since = last_id.and_then(|LastEventId(id)| id.parse().ok()).unwrap_or(0);
+///     let events = subscribe(since).map(|update| {
+///         Ok(Event::new().event("update").id(update.seq.to_string()).data(update.text))
+///     });
+///     Sse::new(events).keep_alive(KeepAlive::new())
+/// }
+/// ```
+///
+/// The stream ending with an error closes the connection, which the client retries.
+///
+/// [`LastEventId`]: crate::headers::LastEventId
+#[must_use]
+pub struct Sse<S> {
+    stream: S,
+    keep_alive: Option<KeepAlive>,
+}
+
+impl<S> Sse<S> {
+    pub fn new(stream: S) -> Self {
+        Self {
+            stream,
+            keep_alive: None,
+        }
+    }
+
+    /// Send a comment whenever no event was sent for a while, so that proxies don't close an
+    /// idle connection.
+    pub fn keep_alive(mut self, keep_alive: KeepAlive) -> Self {
+        self.keep_alive = Some(keep_alive);
+        self
+    }
+}
+
+impl<S, E> IntoResponse for Sse<S>
+where
+    S: Stream<Item = Result<Event, E>> + 'static,
+    E: Into<BoxError> + 'static,
+{
+    fn into_response(self) -> Response {
+        let chunks = futures::stream::unfold(
+            (Box::pin(self.stream), self.keep_alive),
+            async |(mut events, keep_alive)| {
+                let chunk = match &keep_alive {
+                    Some(keep_alive) => {
+                        match compio::time::timeout(keep_alive.interval, events.next()).await {
+                            Ok(next) => next?.map(Event::into_bytes),
+                            Err(_) => Ok(keep_alive.ping.clone()),
+                        }
+                    }
+                    None => events.next().await?.map(Event::into_bytes),
+                };
+                Some((chunk, (events, keep_alive)))
+            },
+        );
+
+        let mut resp = Response::new(Body::from_stream(chunks));
+        resp.headers_mut().insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static(mime::TEXT_EVENT_STREAM.as_ref()),
+        );
+        resp.headers_mut()
+            .insert(header::CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+        resp
+    }
+}
+
+impl<S> fmt::Debug for Sse<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sse")
+            .field("keep_alive", &self.keep_alive)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A message of a [`Sse`] stream.
+///
+/// Every field is optional, a client only dispatches events with data though.
+#[derive(Clone, Debug, Default)]
+#[must_use]
+pub struct Event {
+    buf: BytesMut,
+}
+
+impl Event {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Add data, sent on as many `data:` lines as needed. The client joins the data of an
+    /// event with newlines.
+    pub fn data(mut self, data: impl AsRef<str>) -> Self {
+        for line in lines(data.as_ref()) {
+            self.field("data", line);
+        }
+        self
+    }
+
+    /// Add `value` serialized as JSON as data.
+    pub fn json_data(self, value: impl Serialize) -> Result<Self, serde_json::Error> {
+        Ok(self.data(serde_json::to_string(&value)?))
+    }
+
+    /// Set the event type, dispatched to the listeners of `name` instead of `message`.
+    ///
+    /// # Panics
+    ///
+    /// If `name` contains a newline.
+    pub fn event(mut self, name: impl AsRef<str>) -> Self {
+        self.field("event", single_line("event", name.as_ref()));
+        self
+    }
+
+    /// Set the id the client sends back as `Last-Event-ID` when it reconnects.
+    ///
+    /// # Panics
+    ///
+    /// If `id` contains a newline or a null character.
+    pub fn id(mut self, id: impl AsRef<str>) -> Self {
+        let id = single_line("id", id.as_ref());
+        assert!(
+            !id.contains('\0'),
+            "an event id cannot contain a null character"
+        );
+        self.field("id", id);
+        self
+    }
+
+    /// Set how long the client waits before reconnecting after the connection is lost.
+    pub fn retry(mut self, delay: Duration) -> Self {
+        self.field("retry", &delay.as_millis().to_string());
+        self
+    }
+
+    /// Add a comment, ignored by the client.
+    pub fn comment(mut self, comment: impl AsRef<str>) -> Self {
+        for line in lines(comment.as_ref()) {
+            self.field("", line);
+        }
+        self
+    }
+
+    fn field(&mut self, name: &str, value: &str) {
+        self.buf.put_slice(name.as_bytes());
+        self.buf.put_u8(b':');
+        if !value.is_empty() {
+            self.buf.put_u8(b' ');
+            self.buf.put_slice(value.as_bytes());
+        }
+        self.buf.put_u8(b'\n');
+    }
+
+    /// The event as sent, terminated by a blank line.
+    fn into_bytes(mut self) -> Bytes {
+        self.buf.put_u8(b'\n');
+        self.buf.freeze()
+    }
+}
+
+fn lines(value: &str) -> impl Iterator<Item = &str> {
+    value
+        .split("\r\n")
+        .flat_map(|line| line.split(['\n', '\r']))
+}
+
+fn single_line<'a>(field: &str, value: &'a str) -> &'a str {
+    assert!(
+        !value.contains(['\n', '\r']),
+        "an event {field} cannot contain a newline"
+    );
+    value
+}
+
+/// The comment sent by [`Sse::keep_alive`] on an idle stream.
+#[derive(Clone, Debug)]
+pub struct KeepAlive {
+    interval: Duration,
+    ping: Bytes,
+}
+
+impl KeepAlive {
+    /// An empty comment every 15 seconds.
+    pub fn new() -> Self {
+        Self {
+            interval: Duration::from_secs(15),
+            ping: Bytes::from_static(b":\n\n"),
+        }
+    }
+
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Send `text` as the comment.
+    pub fn text(mut self, text: impl AsRef<str>) -> Self {
+        self.ping = Event::new().comment(text).into_bytes();
+        self
+    }
+}
+
+impl Default for KeepAlive {
+    fn default() -> Self {
+        Self::new()
+    }
+}
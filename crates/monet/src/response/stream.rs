@@ -0,0 +1,110 @@
+use std::fmt;
+
+use bytes::Bytes;
+use futures::{Stream, StreamExt, TryStreamExt, future, stream};
+use http::{HeaderMap, HeaderName, HeaderValue, header};
+use http_body::Frame;
+
+use crate::{
+    BoxError,
+    body::Body,
+    response::{IntoResponse, Response},
+};
+
+type MakeTrailers = Box<dyn FnOnce() -> HeaderMap>;
+
+/// A response sending the chunks of a stream as they are produced, without buffering them.
+///
+/// The stream is only polled when the connection is ready for more data, so a slow client
+/// slows the producer down instead of letting data pile up in memory. A producer running on
+/// its own task can feed a bounded channel and respond with its receiver:
+///
+/// ```ignore
+/// async fn report() -> impl IntoResponse {
+///     let (mut tx, rx) = futures::channel::mpsc::channel::<Bytes>(8);
+///     compio::runtime::spawn(async move {
+///         for row in rows() {
+///             // Waits while the 8 pending chunks are not sent yet.
+///             if tx.send(row.to_csv()).await.is_err() {
+///                 break;
+///             }
+///         }
+///     })
+///     .detach();
+///     StreamBody::new(rx.map(Ok::<_, Infallible>)).content_type("text/csv")
+/// }
+/// ```
+///
+/// The stream ending with an error aborts the response.
+#[must_use]
+pub struct StreamBody<S> {
+    stream: S,
+    content_type: HeaderValue,
+    trailers: Option<(Vec<HeaderName>, MakeTrailers)>,
+}
+
+impl<S> StreamBody<S> {
+    /// Send `stream` as `application/octet-stream`.
+    pub fn new(stream: S) -> Self {
+        Self {
+            stream,
+            content_type: HeaderValue::from_static(mime::APPLICATION_OCTET_STREAM.as_ref()),
+            trailers: None,
+        }
+    }
+
+    pub fn content_type(mut self, content_type: &'static str) -> Self {
+        self.content_type = HeaderValue::from_static(content_type);
+        self
+    }
+
+    /// Send trailers built by `trailers` once the stream is over, e.g. a checksum of the data.
+    ///
+    /// The `names` are announced upfront in the `Trailer` header, other fields are dropped.
+    /// Trailers are only sent to HTTP/1.1 clients asking for them with `TE: trailers`.
+    pub fn trailers<I>(mut self, names: I, trailers: impl FnOnce() -> HeaderMap + 'static) -> Self
+    where
+        I: IntoIterator<Item = HeaderName>,
+    {
+        self.trailers = Some((names.into_iter().collect(), Box::new(trailers)));
+        self
+    }
+}
+
+impl<S, D, E> IntoResponse for StreamBody<S>
+where
+    S: Stream<Item = Result<D, E>> + 'static,
+    D: Into<Bytes>,
+    E: Into<BoxError> + 'static,
+{
+    fn into_response(self) -> Response {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::CONTENT_TYPE, self.content_type);
+        let make_trailers = self.trailers.map(|(names, make_trailers)| {
+            for name in names {
+                headers.append(header::TRAILER, name.into());
+            }
+            make_trailers
+        });
+
+        let trailers = stream::once(async move {
+            make_trailers.map(|make_trailers| Ok(Frame::trailers(make_trailers())))
+        })
+        .filter_map(future::ready);
+        let frames = self
+            .stream
+            .map_ok(|data| Frame::data(data.into()))
+            .chain(trailers);
+        let mut resp = Response::new(Body::from_frames(frames));
+        *resp.headers_mut() = headers;
+        resp
+    }
+}
+
+impl<S> fmt::Debug for StreamBody<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StreamBody")
+            .field("content_type", &self.content_type)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,214 @@
+use http::{StatusCode, header};
+use http_body_util::BodyExt;
+use thiserror::Error as ThisError;
+
+use crate::{IntoResponse, Response};
+
+#[derive(Debug, ThisError, crate::IntoResponse)]
+enum ShopError {
+    #[error("order {0} not found")]
+    #[status(404)]
+    OrderNotFound(u32),
+
+    #[error("the shop is closed")]
+    #[status(503)]
+    #[header("retry-after", "3600")]
+    Closed,
+
+    #[error("invalid quantity {quantity}")]
+    #[status(422)]
+    #[json]
+    InvalidQuantity { quantity: i32 },
+
+    #[error("database is down")]
+    Database,
+}
+
+#[derive(Debug, ThisError, crate::IntoResponse)]
+#[error("slow down")]
+#[status(429)]
+#[json]
+struct RateLimited;
+
+async fn body(resp: Response) -> String {
+    let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+    String::from_utf8(bytes.to_vec()).unwrap()
+}
+
+#[compio::test]
+async fn derive_into_response() {
+    let resp = ShopError::OrderNotFound(7).into_response();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    assert_eq!(body(resp).await, "order 7 not found");
+
+    let resp = ShopError::Closed.into_response();
+    assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(resp.headers()[header::RETRY_AFTER], "3600");
+
+    let resp = ShopError::InvalidQuantity { quantity: -1 }.into_response();
+    assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(resp.headers()[header::CONTENT_TYPE], "application/json");
+    assert_eq!(body(resp).await, r#"{"error":"invalid quantity -1"}"#);
+
+    let resp = ShopError::Database.into_response();
+    assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+    let resp = RateLimited.into_response();
+    assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(body(resp).await, r#"{"error":"slow down"}"#);
+}
+
+#[compio::test]
+async fn negotiate() {
+    use http::{HeaderValue, Method};
+
+    use crate::{Router, get, headers::Accept, response::Negotiate, router::tests::request};
+
+    #[derive(serde::Serialize)]
+    struct User {
+        name: &'static str,
+    }
+
+    async fn show(accept: Accept) -> Negotiate<User> {
+        Negotiate::new(accept, User { name: "ann" })
+            .html(|user| format!("<h1>{}</h1>", user.name))
+            .text(|user| user.name.to_string())
+    }
+
+    let app = Router::new().at("/", get(show));
+    let send = async |accept: Option<&'static str>| {
+        let mut req = request(Method::GET, "/");
+        if let Some(accept) = accept {
+            req.headers_mut()
+                .insert(header::ACCEPT, HeaderValue::from_static(accept));
+        }
+        let resp = app.handle(req).await;
+        let content_type = resp.headers().get(header::CONTENT_TYPE).cloned();
+        assert_eq!(resp.headers()[header::VARY], "accept");
+        (resp.status(), content_type, body(resp).await)
+    };
+
+    let (status, content_type, text) = send(None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type.unwrap(), "application/json");
+    assert_eq!(text, r#"{"name":"ann"}"#);
+
+    let (_, content_type, text) = send(Some("text/html,application/xml;q=0.9,*/*;q=0.8")).await;
+    assert_eq!(content_type.unwrap(), "text/html; charset=utf-8");
+    assert_eq!(text, "<h1>ann</h1>");
+
+    let (_, _, text) = send(Some("text/plain, application/json;q=0.5")).await;
+    assert_eq!(text, "ann");
+
+    let (_, _, text) = send(Some("application/x-www-form-urlencoded")).await;
+    assert_eq!(text, "name=ann");
+
+    let (status, _, text) = send(Some("image/png")).await;
+    assert_eq!(status, StatusCode::NOT_ACCEPTABLE);
+    assert!(text.contains("application/json"));
+}
+
+#[compio::test]
+async fn stream_body() {
+    use std::{cell::Cell, convert::Infallible, rc::Rc};
+
+    use bytes::Bytes;
+    use futures::StreamExt;
+    use http::{HeaderMap, HeaderName};
+    use http_body::Body as _;
+
+    use crate::{StreamBody, body::Body};
+
+    let size = Rc::new(Cell::new(0));
+    let counted = size.clone();
+    let chunks = futures::stream::iter(["id,total\n", "1,30\n"]).map(move |row| {
+        counted.set(counted.get() + row.len());
+        Ok::<_, Infallible>(row)
+    });
+    let resp = StreamBody::new(chunks)
+        .content_type("text/csv")
+        .trailers([HeaderName::from_static("x-size")], move || {
+            let mut trailers = HeaderMap::new();
+            trailers.insert("x-size", size.get().into());
+            trailers
+        })
+        .into_response();
+    assert_eq!(resp.headers()[header::CONTENT_TYPE], "text/csv");
+    assert_eq!(resp.headers()[header::TRAILER], "x-size");
+    let collected = resp.into_body().collect().await.unwrap();
+    assert_eq!(collected.trailers().unwrap()["x-size"], "14");
+    assert_eq!(collected.to_bytes(), "id,total\n1,30\n");
+
+    static DATA: [u8; 40_000] = [7; 40_000];
+    let mut body = Body::from_reader(&DATA[..]);
+    let mut sizes = Vec::new();
+    while let Some(frame) =
+        std::future::poll_fn(|cx| std::pin::Pin::new(&mut body).poll_frame(cx)).await
+    {
+        sizes.push(frame.unwrap().into_data().unwrap().len());
+    }
+    assert_eq!(sizes, [16_384, 16_384, 7_232]);
+
+    let failing = futures::stream::iter([Ok(Bytes::from("partial")), Err("upstream closed")]);
+    let resp = Body::from_stream(failing).into_response();
+    assert!(resp.into_body().collect().await.is_err());
+}
+
+#[compio::test]
+async fn sse() {
+    use std::{convert::Infallible, time::Duration};
+
+    use http::Method;
+
+    use crate::{
+        Router, get,
+        headers::LastEventId,
+        response::{Event, KeepAlive, Sse},
+        router::tests::request,
+    };
+
+    let events = futures::stream::iter([
+        Ok::<_, Infallible>(
+            Event::new()
+                .event("update")
+                .id("7")
+                .retry(Duration::from_secs(3))
+                .data("line 1\nline 2\r\n"),
+        ),
+        Ok(Event::new().comment("note").json_data([1, 2]).unwrap()),
+    ]);
+    let resp = Sse::new(events).into_response();
+    assert_eq!(resp.headers()[header::CONTENT_TYPE], "text/event-stream");
+    assert_eq!(resp.headers()[header::CACHE_CONTROL], "no-cache");
+    assert_eq!(
+        body(resp).await,
+        "event: update\nid: 7\nretry: 3000\ndata: line 1\ndata: line 2\ndata:\n\n\
+         : note\ndata: [1,2]\n\n"
+    );
+
+    let late = futures::stream::once(async {
+        compio::time::sleep(Duration::from_millis(50)).await;
+        Ok::<_, Infallible>(Event::new().data("late"))
+    });
+    let keep_alive = KeepAlive::new()
+        .interval(Duration::from_millis(20))
+        .text("ping");
+    let sent = body(Sse::new(late).keep_alive(keep_alive).into_response()).await;
+    assert!(sent.starts_with(": ping\n\n: ping\n\n"), "{sent}");
+    assert!(sent.ends_with("\n\ndata: late\n\n"), "{sent}");
+
+    async fn resume(last_id: Option<LastEventId>) -> String {
+        format!("{last_id:?}")
+    }
+
+    let app = Router::new().at("/events", get(resume));
+    let mut req = request(Method::GET, "/events");
+    req.headers_mut()
+        .insert("last-event-id", "42".parse().unwrap());
+    assert_eq!(
+        body(app.handle(req).await).await,
+        r#"Some(LastEventId("42"))"#
+    );
+    let resp = app.handle(request(Method::GET, "/events")).await;
+    assert_eq!(body(resp).await, "None");
+}
@@ -1,8 +1,13 @@
 #[cfg(test)]
 pub(crate) mod tests;
 
+mod constraint;
+pub(crate) mod error_hook;
 pub(crate) mod url;
 
+pub use constraint::Constraint;
+pub use error_hook::{FailedRequest, Failure};
+
 use core::panic;
 use std::{
     collections::{HashMap, hash_map::Entry},
@@ -11,31 +16,58 @@ use std::{
     sync::Arc,
 };
 
-use http::Method;
+use futures::future::{self, LocalBoxFuture};
+use http::{HeaderValue, Method, StatusCode, header};
 
 use crate::{
     GUARANTEE, ServeDir,
-    handler::{Endpoint, Layer, Middleware, middleware::strip_prefix::StripPrefix},
-    request::Request,
-    response::Response,
-    router::url::{NEST_TAIL_PARAM, concat_path, insert_matched_params, insert_matched_path},
+    handler::{Endpoint, Handler, Layer, Middleware, middleware::strip_prefix::StripPrefix},
+    request::{AppStateMap, Request, StateKey},
+    response::{IntoResponse, Response},
+    router::{
+        constraint::{ParamConstraints, strip_constraints},
+        error_hook::ErrorHook,
+        url::{
+            NEST_TAIL_PARAM, concat_path, insert_matched_params, insert_matched_path, pct_decode,
+        },
+    },
 };
 
-pub fn get(handler: impl Endpoint) -> Route {
+/// Build a [`Router`] out of handlers annotated with a route attribute such as `#[get("/")]`.
+///
+/// ```ignore
+/// let app = Router::new().nest("/api", routes![list_users, show_user, create_user]);
+/// ```
+#[macro_export]
+macro_rules! routes {
+    ($($endpoint:expr),* $(,)?) => {
+        $crate::Router::new()$(.mount($endpoint))*
+    };
+}
+
+/// An [`Endpoint`] that knows the path and method it serves, so it can be added to a router
+/// with [`Router::mount`]. Implemented by the route attribute macros, e.g. `#[get("/")]`.
+pub trait RouteEndpoint: Endpoint {
+    /// The route pattern, with the same syntax as [`Router::at`].
+    const PATH: &'static str;
+    const METHOD: Method;
+}
+
+pub fn get<T>(handler: impl Handler<T>) -> Route {
     let mut md = MethodDispatch::new();
     md.register(handler, Method::GET);
 
     Route::MethodDispatch(md)
 }
 
-pub fn post(handler: impl Endpoint) -> Route {
+pub fn post<T>(handler: impl Handler<T>) -> Route {
     let mut md = MethodDispatch::new();
     md.register(handler, Method::POST);
 
     Route::MethodDispatch(md)
 }
 
-pub fn catch(handler: impl Endpoint) -> Route {
+pub fn catch<T>(handler: impl Handler<T>) -> Route {
     let mut md = MethodDispatch::new();
     md.fallback(handler);
 
@@ -50,6 +82,9 @@ pub struct Router {
     pub index_to_path: HashMap<usize, Arc<str>>,
     pub middlewares: Rc<Vec<Rc<dyn Middleware>>>,
     pub fallback: Option<Rc<dyn Endpoint>>,
+    pub constraints: HashMap<usize, ParamConstraints>,
+    pub app_state: AppStateMap,
+    error_hook: Option<ErrorHook>,
 }
 
 impl Router {
@@ -57,18 +92,49 @@ impl Router {
         Default::default()
     }
 
-    pub fn handle(&self, mut req: Request) -> impl Future<Output = Response> {
+    pub fn handle(&self, req: Request) -> impl Future<Output = Response> {
+        let hook = self.error_hook.clone().map(|hook| {
+            let failed = FailedRequest {
+                method: req.method().clone(),
+                uri: req.uri().clone(),
+                version: *req.version(),
+                headers: req.headers().clone(),
+            };
+            (hook, failed)
+        });
+        let resp = self.dispatch(req);
+        async move {
+            let resp = resp.await;
+            match hook {
+                Some((hook, failed)) => hook.apply(&failed, resp),
+                None => resp,
+            }
+        }
+    }
+
+    fn dispatch(&self, mut req: Request) -> LocalBoxFuture<'_, Response> {
+        req.app_state = self.app_state.clone();
+
         let request_path = req.uri().path().to_string();
 
         let Ok(matched) = self.inner.at(request_path.as_str()) else {
-            match &self.fallback {
-                Some(handler) => return handler.call(req),
-                None => panic!("Path {} not found", request_path),
-            }
+            return self.not_found(req);
         };
 
         let index = *matched.value;
 
+        if let Some(constraints) = self.constraints.get(&index)
+            && !constraints.iter().all(|(name, constraint)| {
+                matched
+                    .params
+                    .get(name)
+                    .and_then(pct_decode)
+                    .is_some_and(|value| constraint.matches(&value))
+            })
+        {
+            return self.not_found(req);
+        }
+
         let ext_mut = req.extensions_mut();
 
         // #[cfg(not(feature = "no-matched-path"))]
@@ -94,7 +160,7 @@ impl Router {
                 Some(layer) => layer.clone().next(req),
                 None => match &dispatch.fallback {
                     Some(handler) => return handler.call(req),
-                    None => panic!("No handler for {} Method at Route {}", method, request_path),
+                    None => return Box::pin(future::ready(dispatch.method_not_allowed())),
                 },
             },
         };
@@ -102,17 +168,57 @@ impl Router {
         Box::pin(resp_fut)
     }
 
-    pub fn at(mut self, path: &str, other_route: Route) -> Self {
-        if let Some(index) = self.path_to_index.get(path) {
-            let existing_route = self.routes.get_mut(*index).unwrap();
+    pub fn at(self, path: &str, other_route: Route) -> Self {
+        let (path, constraints) = strip_constraints(path);
+        self.at_constrained(&path, other_route, constraints)
+    }
+
+    /// The constraints of a path apply to all its methods, so a path registered again has to
+    /// come with the same constraints.
+    fn at_constrained(
+        mut self,
+        path: &str,
+        other_route: Route,
+        constraints: ParamConstraints,
+    ) -> Self {
+        if let Some(&index) = self.path_to_index.get(path) {
+            let existing = self.constraints.get(&index).map_or(&[][..], Vec::as_slice);
+            if existing != constraints.as_slice() {
+                panic!(
+                    "Conflicting constraints. Route `{path}` is already registered with other constraints"
+                )
+            }
+            let existing_route = self.routes.get_mut(index).unwrap();
             existing_route.merge(other_route);
         } else {
-            self.new_route(path, other_route);
+            let index = self.new_route(path, other_route);
+            self.add_constraints(index, constraints);
         }
 
         self
     }
 
+    /// Add an endpoint at the path and method it declares. See [`RouteEndpoint`].
+    pub fn mount<E: RouteEndpoint>(self, endpoint: E) -> Self {
+        let mut md = MethodDispatch::new();
+        md.register(endpoint, E::METHOD);
+        self.at(E::PATH, Route::MethodDispatch(md))
+    }
+
+    /// Restrict the values the path parameter `param` of the route at `path` may take, for all
+    /// its methods. Add every method of the path before constraining it.
+    ///
+    /// See [`Constraint`] for how non-matching requests are handled.
+    pub fn constrain(mut self, path: &str, param: &str, constraint: Constraint) -> Self {
+        let (path, _) = strip_constraints(path);
+        let Some(&index) = self.path_to_index.get(path.as_str()) else {
+            panic!("Cannot constrain `{param}` of unknown route `{path}`");
+        };
+        self.add_constraints(index, vec![(param.into(), constraint)]);
+
+        self
+    }
+
     pub fn merge(mut self, other: Self) -> Self {
         // Merge fallback
         match (&self.fallback, &other.fallback) {
@@ -122,11 +228,13 @@ impl Router {
                 panic!("Cannot merge two `Router`s that both have a fallback")
             }
         }
+        self.app_state.merge(&other.app_state);
+        self.error_hook = self.error_hook.or(other.error_hook);
 
         for (index, route) in other.routes.into_iter().enumerate() {
             let path = other.index_to_path.get(&index).expect(GUARANTEE);
-
-            self = self.at(path, route);
+            let constraints = other.constraints.get(&index).cloned().unwrap_or_default();
+            self = self.at_constrained(path, route, constraints);
         }
         self
     }
@@ -140,12 +248,15 @@ impl Router {
         }) {
             panic!("Invalid route: nested routes cannot contain wildcards (*)");
         }
+        self.app_state.merge(&other.app_state);
+        self.error_hook = self.error_hook.or(other.error_hook);
 
         for (index, route) in other.routes.into_iter().enumerate() {
             let inner_path = other.index_to_path.get(&index).expect(GUARANTEE);
 
-            let new_path = concat_path(prefix, inner_path);
-            self = self.at(&new_path, route);
+            let (new_path, mut constraints) = strip_constraints(&concat_path(prefix, inner_path));
+            constraints.extend(other.constraints.get(&index).cloned().unwrap_or_default());
+            self = self.at_constrained(&new_path, route, constraints);
         }
 
         self
@@ -169,18 +280,91 @@ impl Router {
         self
     }
 
-    pub fn catch_all(mut self, h: impl Endpoint) -> Self {
-        self.fallback = Some(Rc::new(h));
+    /// Produce the response of every request that failed with a [`Failure`]: a rejected
+    /// extractor, an [`Error`] returned by a handler, or a panic caught by [`CatchPanic`].
+    ///
+    /// The hook receives the method, URI and headers of the request, the failure, and the
+    /// response it would otherwise get, so formatting, logging and status codes can be changed
+    /// in one place. The request is only copied for the hook when one is set.
+    ///
+    /// ```ignore
+    /// let app = Router::new()
+    ///     .at("/orders", post(create_order))
+    ///     .on_error(|req, failure, resp| {
+    ///         let request_id = req.headers.get("x-request-id");
+    ///         tracing::warn!(uri = %req.uri, ?request_id, ?failure, "request failed");
+    ///         let envelope = json!({ "code": resp.status().as_u16(), "path": req.uri.path() });
+    ///         (resp.status(), Json(envelope)).into_response()
+    ///     });
+    /// ```
+    ///
+    /// When routers are merged or nested, the outer router's hook wins.
+    ///
+    /// [`Error`]: crate::Error
+    /// [`CatchPanic`]: crate::CatchPanic
+    pub fn on_error<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&FailedRequest, &Failure, Response) -> Response + 'static,
+    {
+        self.error_hook = Some(ErrorHook::new(hook));
+        self
+    }
+
+    pub fn catch_all<T>(mut self, h: impl Handler<T>) -> Self {
+        self.fallback = Some(h.into_endpoint());
+        self
+    }
+
+    /// Make `state` available to every handler and middleware of this router through
+    /// [`Request::app_state`]. A previous state of the same type is replaced.
+    ///
+    /// When routers are merged or nested, state the outer router already has wins over the
+    /// inner router's state of the same type.
+    pub fn with_state<T: 'static>(mut self, state: T) -> Self {
+        self.app_state.insert(state);
         self
     }
 
-    fn new_route(&mut self, path: &str, route: Route) {
+    /// The application state types required by the handlers and middlewares of this router
+    /// that were never provided with [`Router::with_state`].
+    pub fn missing_state(&self) -> Vec<StateKey> {
+        let mut missing = Vec::new();
+        let fallbacks = self.fallback.iter().flat_map(|f| f.required_state());
+        let routes = self.routes.iter().flat_map(|route| route.required_state());
+
+        for key in fallbacks.chain(routes) {
+            if !self.app_state.contains(&key) && !missing.contains(&key) {
+                missing.push(key);
+            }
+        }
+        missing
+    }
+
+    fn new_route(&mut self, path: &str, route: Route) -> usize {
         let new_index = self.routes.len();
         self.inner.insert(path, new_index).expect(GUARANTEE);
 
         self.routes.push(route);
         self.path_to_index.insert(path.into(), new_index);
         self.index_to_path.insert(new_index, path.into());
+
+        new_index
+    }
+
+    fn add_constraints(&mut self, index: usize, constraints: ParamConstraints) {
+        if !constraints.is_empty() {
+            self.constraints
+                .entry(index)
+                .or_default()
+                .extend(constraints);
+        }
+    }
+
+    fn not_found(&self, req: Request) -> LocalBoxFuture<'_, Response> {
+        match &self.fallback {
+            Some(handler) => handler.call(req),
+            None => Box::pin(future::ready(StatusCode::NOT_FOUND.into_response())),
+        }
     }
 }
 
@@ -190,6 +374,12 @@ pub enum Route {
     Service(Layer),
 }
 
+impl Default for Route {
+    fn default() -> Self {
+        Route::new()
+    }
+}
+
 #[derive(Default, Debug, Clone)]
 pub struct MethodDispatch {
     pub inner: HashMap<Method, Layer>,
@@ -197,11 +387,16 @@ pub struct MethodDispatch {
 }
 
 impl Route {
-    pub fn get(self, h: impl Endpoint) -> Self {
-        self.register(h, Method::POST)
+    /// A route without any handler, to add methods to with e.g. [`Route::get`].
+    pub fn new() -> Self {
+        Route::MethodDispatch(MethodDispatch::new())
+    }
+
+    pub fn get<T>(self, h: impl Handler<T>) -> Self {
+        self.register(h, Method::GET)
     }
 
-    pub fn post(self, h: impl Endpoint) -> Self {
+    pub fn post<T>(self, h: impl Handler<T>) -> Self {
         self.register(h, Method::POST)
     }
 
@@ -227,6 +422,12 @@ impl Route {
         }
     }
 
+    /// Wrap the endpoints of this route by `middleware`, inside the middlewares of the router.
+    pub fn wrap(mut self, middleware: impl Middleware) -> Self {
+        self.wrap_by(Rc::new(middleware));
+        self
+    }
+
     pub fn wrap_by(&mut self, middleware: Rc<impl Middleware>) {
         match self {
             Route::MethodDispatch(dispatch) => {
@@ -239,16 +440,28 @@ impl Route {
         }
     }
 
-    pub fn register(mut self, h: impl Endpoint, m: Method) -> Self {
+    fn required_state(&self) -> Vec<StateKey> {
+        match self {
+            Route::MethodDispatch(dispatch) => dispatch
+                .inner
+                .values()
+                .flat_map(|layer| layer.required_state())
+                .chain(dispatch.fallback.iter().flat_map(|f| f.required_state()))
+                .collect(),
+            Route::Service(layer) => layer.required_state().collect(),
+        }
+    }
+
+    pub fn register<T>(mut self, h: impl Handler<T>, m: Method) -> Self {
         if let Route::MethodDispatch(ref mut dispatch) = self {
             dispatch.register(h, m);
         }
         self
     }
 
-    pub fn catch(mut self, h: impl Endpoint) -> Self {
+    pub fn catch<T>(mut self, h: impl Handler<T>) -> Self {
         if let Route::MethodDispatch(ref mut dispatch) = self {
-            dispatch.fallback = Some(Rc::new(h));
+            dispatch.fallback = Some(h.into_endpoint());
         }
         self
     }
@@ -259,15 +472,23 @@ impl MethodDispatch {
         Default::default()
     }
 
-    pub fn fallback(&mut self, h: impl Endpoint) {
-        self.fallback = Some(Rc::new(h));
+    pub fn fallback<T>(&mut self, h: impl Handler<T>) {
+        self.fallback = Some(h.into_endpoint());
     }
 
-    fn register(&mut self, h: impl Endpoint, m: Method) {
-        let layer = Layer {
-            endpoint: Rc::new(h),
-            middlewares: Default::default(),
-        };
+    /// Answer a method without handler nor fallback, listing the handled methods in `Allow`.
+    fn method_not_allowed(&self) -> Response {
+        let mut methods: Vec<_> = self.inner.keys().map(Method::as_str).collect();
+        methods.sort_unstable();
+        let mut resp = StatusCode::METHOD_NOT_ALLOWED.into_response();
+        if let Ok(allow) = HeaderValue::from_str(&methods.join(", ")) {
+            resp.headers_mut().insert(header::ALLOW, allow);
+        }
+        resp
+    }
+
+    fn register<T>(&mut self, h: impl Handler<T>, m: Method) {
+        let layer = Layer::new(h);
         match self.inner.entry(m.clone()) {
             Entry::Vacant(e) => e.insert(layer),
             Entry::Occupied(_) => {
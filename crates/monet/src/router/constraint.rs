@@ -0,0 +1,145 @@
+use std::{str::FromStr, sync::Arc};
+
+use regex::Regex;
+
+/// A restriction on the value a path parameter may take.
+///
+/// A request whose parameter doesn't satisfy the constraint is treated as if the route didn't
+/// match at all: it goes to the router's fallback, or gets a `404 Not Found` if there is none.
+///
+/// Constraints can be written inline in the route pattern, e.g. `/user/{id:int}`,
+/// `/file/{id:uuid}` or `/issues/{state:open|closed}`, or attached with [`Router::constrain`].
+/// Regular expressions can only be attached with the builder, since they may contain braces.
+///
+/// The constraints of a path apply to all its methods: registering the same path again with
+/// different constraints panics.
+///
+/// [`Router::constrain`]: crate::Router::constrain
+#[derive(Clone, Debug)]
+pub enum Constraint {
+    /// A signed decimal integer that fits in an `i64`. Pattern syntax: `{name:int}`.
+    Int,
+    /// An unsigned decimal integer that fits in an `u64`. Pattern syntax: `{name:uint}`.
+    Uint,
+    /// A hyphenated UUID such as `67e55044-10b1-426f-9247-bb680e5fe0c8`. Pattern syntax:
+    /// `{name:uuid}`.
+    Uuid,
+    /// One of a fixed set of values. Pattern syntax: `{name:a|b|c}`.
+    OneOf(Vec<Arc<str>>),
+    /// A regular expression that has to match the whole value.
+    Regex(Regex),
+}
+
+impl Constraint {
+    /// Build a [`Constraint::Regex`]. The expression is anchored at both ends.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `re` is not a valid regular expression.
+    pub fn regex(re: &str) -> Self {
+        match Regex::new(&format!("^(?:{re})$")) {
+            Ok(re) => Self::Regex(re),
+            Err(err) => panic!("Invalid regex constraint `{re}`: {err}"),
+        }
+    }
+
+    /// Build a [`Constraint::OneOf`] from a set of accepted values.
+    pub fn one_of<I, S>(values: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<Arc<str>>,
+    {
+        Self::OneOf(values.into_iter().map(Into::into).collect())
+    }
+
+    /// Check the percent-decoded value of a path parameter against this constraint.
+    pub fn matches(&self, value: &str) -> bool {
+        match self {
+            Self::Int => value.parse::<i64>().is_ok(),
+            Self::Uint => value.parse::<u64>().is_ok(),
+            Self::Uuid => is_uuid(value),
+            Self::OneOf(values) => values.iter().any(|v| &**v == value),
+            Self::Regex(re) => re.is_match(value),
+        }
+    }
+}
+
+impl FromStr for Constraint {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "int" => Ok(Self::Int),
+            "uint" => Ok(Self::Uint),
+            "uuid" => Ok(Self::Uuid),
+            _ if s.contains('|') => Ok(Self::one_of(s.split('|'))),
+            _ => Err(format!(
+                "unknown constraint `{s}`, expected `int`, `uint`, `uuid` or `a|b|...`"
+            )),
+        }
+    }
+}
+
+impl PartialEq for Constraint {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Int, Self::Int) | (Self::Uint, Self::Uint) | (Self::Uuid, Self::Uuid) => true,
+            (Self::OneOf(a), Self::OneOf(b)) => a == b,
+            (Self::Regex(a), Self::Regex(b)) => a.as_str() == b.as_str(),
+            _ => false,
+        }
+    }
+}
+
+fn is_uuid(value: &str) -> bool {
+    let bytes = value.as_bytes();
+    bytes.len() == 36
+        && bytes.iter().enumerate().all(|(i, b)| match i {
+            8 | 13 | 18 | 23 => *b == b'-',
+            _ => b.is_ascii_hexdigit(),
+        })
+}
+
+pub(crate) type ParamConstraints = Vec<(Arc<str>, Constraint)>;
+
+/// Split the inline constraints out of a route pattern.
+///
+/// `/user/{id:int}/{*rest}` becomes `/user/{id}/{*rest}`, which is what `matchit` understands,
+/// plus `[("id", Constraint::Int)]`.
+pub(crate) fn strip_constraints(path: &str) -> (String, ParamConstraints) {
+    let mut stripped = String::with_capacity(path.len());
+    let mut constraints = Vec::new();
+    let mut rest = path;
+
+    while let Some(start) = rest.find('{') {
+        // `{{` is an escaped brace, not a parameter
+        if rest[start..].starts_with("{{") {
+            stripped.push_str(&rest[..start + 2]);
+            rest = &rest[start + 2..];
+            continue;
+        }
+
+        let Some(len) = rest[start..].find('}') else {
+            break;
+        };
+        let param = &rest[start + 1..start + len];
+        stripped.push_str(&rest[..start]);
+
+        match param.split_once(':') {
+            Some((name, spec)) => {
+                let constraint = spec
+                    .parse()
+                    .unwrap_or_else(|err| panic!("Invalid route `{path}`: {err}"));
+                stripped.push('{');
+                stripped.push_str(name);
+                stripped.push('}');
+                constraints.push((Arc::from(name.trim_start_matches('*')), constraint));
+            }
+            None => stripped.push_str(&rest[start..=start + len]),
+        }
+        rest = &rest[start + len + 1..];
+    }
+    stripped.push_str(rest);
+
+    (stripped, constraints)
+}
@@ -0,0 +1,87 @@
+use std::{fmt, rc::Rc, sync::Arc};
+
+use http::{HeaderMap, Method, Uri, Version};
+
+use crate::{
+    error::{Error, FieldErrors},
+    problem::ProblemDetails,
+    response::Response,
+};
+
+/// A failure reported to the hook set with [`Router::on_error`].
+///
+/// [`Router::on_error`]: crate::Router::on_error
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Failure {
+    /// An extractor or a handler returned an [`Error`].
+    Rejection(Error),
+    /// An extractor derived with `#[derive(FromRequest)]` failed.
+    FieldErrors(FieldErrors),
+    /// A handler panicked and the panic was caught by [`CatchPanic`], with the panic message.
+    ///
+    /// [`CatchPanic`]: crate::CatchPanic
+    Panic(String),
+}
+
+impl Failure {
+    /// The problem details of the failure, if it has any.
+    pub(crate) fn problem(&self) -> Option<ProblemDetails> {
+        match self {
+            Self::Rejection(err) => Some(err.problem()),
+            Self::FieldErrors(errors) => Some(errors.problem()),
+            Self::Panic(_) => None,
+        }
+    }
+}
+
+/// The request a [`Failure`] happened on, as seen by the hook set with [`Router::on_error`].
+///
+/// It holds the head of the request, without its extensions.
+///
+/// [`Router::on_error`]: crate::Router::on_error
+#[derive(Clone, Debug)]
+pub struct FailedRequest {
+    pub method: Method,
+    pub uri: Uri,
+    pub version: Version,
+    pub headers: HeaderMap,
+}
+
+/// The failure behind a response, kept in its extensions until the hook sees it.
+#[derive(Clone)]
+pub(crate) struct CaughtFailure(pub(crate) Arc<Failure>);
+
+impl CaughtFailure {
+    pub(crate) fn new(failure: Failure) -> Self {
+        Self(Arc::new(failure))
+    }
+}
+
+type HookFn = dyn Fn(&FailedRequest, &Failure, Response) -> Response;
+
+#[derive(Clone)]
+pub(crate) struct ErrorHook(Rc<HookFn>);
+
+impl ErrorHook {
+    pub(crate) fn new<F>(hook: F) -> Self
+    where
+        F: Fn(&FailedRequest, &Failure, Response) -> Response + 'static,
+    {
+        Self(Rc::new(hook))
+    }
+
+    /// Hand the response to the hook if it comes from a failure.
+    pub(crate) fn apply(&self, req: &FailedRequest, mut resp: Response) -> Response {
+        match resp.extensions_mut().remove::<CaughtFailure>() {
+            Some(CaughtFailure(failure)) => (self.0)(req, &failure, resp),
+            None => resp,
+        }
+    }
+}
+
+impl fmt::Debug for ErrorHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ErrorHook").finish_non_exhaustive()
+    }
+}
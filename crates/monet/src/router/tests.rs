@@ -1,6 +1,14 @@
-use http::StatusCode;
+use async_trait::async_trait;
+use http::{Method, StatusCode};
+use http_body_util::BodyExt;
 
-use crate::{Request, Router, get, post};
+use crate::{
+    Endpoint, Request, Response, Router,
+    body::Body,
+    get, post,
+    request::{AppStateMap, State, StateKey},
+    router::{Constraint, Route},
+};
 
 #[test]
 #[should_panic(expected = "Overlapping route. Cannot add two endpoints that both handle `GET`")]
@@ -26,6 +34,243 @@ fn merge_test3() {
     app1.merge(app2);
 }
 
+#[compio::test]
+async fn path_constraints() {
+    let app = Router::new()
+        .at("/user/{id:int}", get(hello))
+        .at("/issues/{state:open|closed}", get(hello))
+        .at("/slug/{slug}", get(hello))
+        .constrain("/slug/{slug}", "slug", Constraint::regex("[a-z-]+"));
+
+    let status = async |uri| app.handle(request(Method::GET, uri)).await.status();
+
+    assert_eq!(status("/user/42").await, StatusCode::OK);
+    assert_eq!(status("/user/abc").await, StatusCode::NOT_FOUND);
+    assert_eq!(status("/issues/open").await, StatusCode::OK);
+    assert_eq!(status("/issues/merged").await, StatusCode::NOT_FOUND);
+    assert_eq!(status("/slug/hello-world").await, StatusCode::OK);
+    assert_eq!(status("/slug/Hello").await, StatusCode::NOT_FOUND);
+}
+
+#[test]
+#[should_panic(expected = "Conflicting constraints. Route `/user/{id}` is already registered")]
+fn conflicting_path_constraints() {
+    let _ = Router::new()
+        .at("/user/{id:int}", get(hello))
+        .at("/user/{id:uuid}", post(hi));
+}
+
+#[compio::test]
+async fn path_constraints_shared_by_methods() {
+    let app = Router::new()
+        .at("/user/{id:int}", get(hello))
+        .at("/user/{id:int}", post(hi));
+
+    let status = async |method, uri| app.handle(request(method, uri)).await.status();
+    assert_eq!(status(Method::GET, "/user/1").await, StatusCode::OK);
+    assert_eq!(status(Method::POST, "/user/1").await, StatusCode::OK);
+    assert_eq!(status(Method::POST, "/user/x").await, StatusCode::NOT_FOUND);
+}
+
+#[compio::test]
+async fn route_methods() {
+    let app = Router::new().at("/user", Route::new().get(hello));
+
+    let resp = app.handle(request(Method::GET, "/user")).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let resp = app.handle(request(Method::POST, "/user")).await;
+    assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(resp.headers()[http::header::ALLOW], "GET");
+
+    let app = Router::new().at("/user", Route::new().post(hi).get(hello));
+    let resp = app.handle(request(Method::DELETE, "/user")).await;
+    assert_eq!(resp.headers()[http::header::ALLOW], "GET, POST");
+}
+
+#[compio::test]
+async fn nested_path_constraints() {
+    let api = Router::new().at("/user/{id:uuid}", get(hello));
+    let app = Router::new()
+        .nest("/api/{version:v1|v2}", api)
+        .catch_all(notfound);
+
+    let status = async |uri| app.handle(request(Method::GET, uri)).await.status();
+
+    assert_eq!(
+        status("/api/v1/user/67e55044-10b1-426f-9247-bb680e5fe0c8").await,
+        StatusCode::OK
+    );
+    assert_eq!(status("/api/v1/user/42").await, StatusCode::NOT_FOUND);
+    assert_eq!(
+        status("/api/v3/user/67e55044-10b1-426f-9247-bb680e5fe0c8").await,
+        StatusCode::NOT_FOUND
+    );
+}
+
+struct Counter(u32);
+
+#[compio::test]
+async fn app_state() {
+    let app = Router::new()
+        .at("/count", get(count))
+        .with_state(Counter(7));
+
+    let resp = app.handle(request(Method::GET, "/count")).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let app = Router::new().at("/count", get(count));
+    let resp = app.handle(request(Method::GET, "/count")).await;
+    assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+}
+
+struct NeedsCounter;
+
+#[async_trait(?Send)]
+impl Endpoint for NeedsCounter {
+    async fn call(&self, req: Request) -> Response {
+        count(req).await
+    }
+
+    fn required_state(&self) -> Vec<StateKey> {
+        vec![StateKey::of::<Counter>()]
+    }
+}
+
+#[test]
+fn missing_app_state() {
+    let app = Router::new().at("/count", get(NeedsCounter));
+    assert_eq!(app.missing_state(), vec![StateKey::of::<Counter>()]);
+
+    let nested = Router::new().nest("/api", app).with_state(Counter(0));
+    assert!(nested.missing_state().is_empty());
+}
+
+#[crate::get("/users/{id:int}")]
+async fn show_user() -> &'static str {
+    "show"
+}
+
+#[crate::post("/users/{id:int}")]
+async fn update_user() -> &'static str {
+    "update"
+}
+
+#[crate::delete("/users")]
+async fn clear_users() -> StatusCode {
+    StatusCode::NO_CONTENT
+}
+
+#[compio::test]
+async fn mount_routes() {
+    use crate::RouteEndpoint;
+
+    assert_eq!(show_user::PATH, "/users/{id:int}");
+    assert_eq!(clear_users::METHOD, Method::DELETE);
+
+    let app = Router::new().nest("/api", crate::routes![show_user, update_user, clear_users]);
+    let status = async |method, uri| app.handle(request(method, uri)).await.status();
+
+    assert_eq!(status(Method::GET, "/api/users/1").await, StatusCode::OK);
+    assert_eq!(status(Method::POST, "/api/users/1").await, StatusCode::OK);
+    assert_eq!(
+        status(Method::GET, "/api/users/x").await,
+        StatusCode::NOT_FOUND
+    );
+    assert_eq!(
+        status(Method::DELETE, "/api/users").await,
+        StatusCode::NO_CONTENT
+    );
+}
+
+#[compio::test]
+async fn error_hook() {
+    use crate::{CatchPanic, IntoResponse, Path, router::Failure};
+
+    #[derive(serde::Deserialize)]
+    struct UserId {
+        #[allow(dead_code)]
+        id: u32,
+    }
+
+    async fn show(_: Path<UserId>) -> &'static str {
+        "user"
+    }
+    async fn boom() -> &'static str {
+        panic!("boom")
+    }
+
+    let app = Router::new()
+        .at("/user/{id}", get(show))
+        .at("/boom", get(boom))
+        .wrap_by(CatchPanic)
+        .on_error(|req, failure, resp| {
+            let kind = match failure {
+                Failure::Rejection(err) => format!("rejection {}", err.status_code().as_u16()),
+                Failure::Panic(message) => format!("panic {message}"),
+                _ => "other".to_string(),
+            };
+            let id = req.headers["x-request-id"].to_str().unwrap();
+            let body = format!(
+                "{} {} ({id}): {kind}",
+                resp.status().as_u16(),
+                req.uri.path()
+            );
+            (StatusCode::IM_A_TEAPOT, body).into_response()
+        });
+
+    let body = async |uri| {
+        let mut req = request(Method::GET, uri);
+        req.headers_mut()
+            .insert("x-request-id", http::HeaderValue::from_static("7"));
+        let resp = app.handle(req).await;
+        let status = resp.status();
+        let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+        (status, String::from_utf8(bytes.to_vec()).unwrap())
+    };
+
+    assert_eq!(body("/user/1").await, (StatusCode::OK, "user".to_string()));
+    assert_eq!(
+        body("/user/x").await,
+        (
+            StatusCode::IM_A_TEAPOT,
+            "400 /user/x (7): rejection 400".to_string()
+        )
+    );
+    assert_eq!(
+        body("/boom").await,
+        (
+            StatusCode::IM_A_TEAPOT,
+            "500 /boom (7): panic boom".to_string()
+        )
+    );
+    // Plain responses with an error status aren't failures.
+    assert_eq!(body("/missing").await.0, StatusCode::NOT_FOUND);
+}
+
+pub(crate) fn request(method: Method, uri: &str) -> Request {
+    let (head, ()) = http::Request::builder()
+        .method(method)
+        .uri(uri)
+        .body(())
+        .unwrap()
+        .into_parts();
+
+    Request {
+        body: Body::empty(),
+        head,
+        state: State::default(),
+        app_state: AppStateMap::default(),
+    }
+}
+
+async fn count(req: Request) -> Response {
+    use crate::IntoResponse;
+
+    req.app_state::<Counter>()
+        .map(|counter| counter.0.to_string())
+        .into_response()
+}
+
 async fn hello(_req: Request) -> &'static str {
     "hello"
 }
@@ -1,54 +1,306 @@
+pub mod mesh;
+#[cfg(test)]
+mod tests;
+
 use std::{
+    any::TypeId,
     convert::Infallible,
     future::Future,
     net::SocketAddr,
     ops::DerefMut,
     panic::AssertUnwindSafe,
-    pin::Pin,
+    pin::{Pin, pin},
+    rc::Rc,
+    sync::Arc,
     task::{Context, Poll, ready},
+    thread,
 };
 
 use compio::{
     io::{AsyncRead, AsyncWrite, compat::AsyncStream},
-    net::{TcpListener, TcpStream, UnixListener, UnixStream},
+    net::{SocketOpts, TcpListener, TcpStream, UnixListener, UnixStream},
+};
+use futures::{
+    channel::{mpsc, oneshot},
+    future::{self, LocalBoxFuture},
+    stream::StreamExt,
 };
-use futures::stream::StreamExt;
 use futures_concurrency::future::FutureGroup;
 use futures_util::FutureExt;
 use hyper::{server::conn::http1, service::service_fn};
 use send_wrapper::SendWrapper;
 
-use crate::{GUARANTEE, Router};
+use crate::{
+    GUARANTEE, Router,
+    serve::mesh::{Envelope, Mesh, MessageHandlers, handle_messages, message_handler},
+};
 
 pub fn run(addr: SocketAddr, router: Router) {
     // dbg!(&router);
+    assert_state_provided(&router);
+
     let app = async {
-        let mut listener = compio::net::TcpListener::bind(addr).await.unwrap();
-        let mut group = FutureGroup::new();
-        loop {
-            tokio::select! {
-                biased;
-                stream = listener.accepts() => {
-                    group.insert(AssertUnwindSafe(async {
-                        http1::Builder::new()
-                            .serve_connection(
-                                HyperStream::new(stream.0),
-                                service_fn(async |req| {
-                                    router.handle(req.into()).map(Ok::<_, Infallible>).await
-                                }),
-                            )
-                            .await
-                            .expect(GUARANTEE)
-                    }).catch_unwind());
-                },
-                _ =  group.next(), if !group.is_empty()  => (),
-            }
-        }
+        let listener = compio::net::TcpListener::bind(addr).await.unwrap();
+        serve_connections(listener, &router, future::pending::<()>()).await
     };
     let rt = compio::runtime::Runtime::new().expect("cannot create runtime");
     rt.block_on(app);
 }
 
+/// Accept connections on `listener` and dispatch them to `router` until `shutdown` resolves.
+///
+/// Connections still open at that point are dropped.
+async fn serve_connections<L: Listener>(mut listener: L, router: &Router, shutdown: impl Future) {
+    let mut shutdown = pin!(shutdown);
+    let mut group = FutureGroup::new();
+    loop {
+        tokio::select! {
+            biased;
+            _ = &mut shutdown => break,
+            stream = listener.accepts() => {
+                group.insert(AssertUnwindSafe(async {
+                    http1::Builder::new()
+                        .serve_connection(
+                            HyperStream::new(stream.0),
+                            service_fn(async |req| {
+                                router.handle(req.into()).map(Ok::<_, Infallible>).await
+                            }),
+                        )
+                        .await
+                        .expect(GUARANTEE)
+                }).catch_unwind());
+            },
+            _ =  group.next(), if !group.is_empty()  => (),
+        }
+    }
+}
+
+fn assert_state_provided(router: &Router) {
+    let missing = router
+        .missing_state()
+        .into_iter()
+        .map(|key| key.name())
+        .collect::<Vec<_>>();
+    if !missing.is_empty() {
+        panic!(
+            "Handlers require application state that was never provided with `Router::with_state`: {}",
+            missing.join(", ")
+        );
+    }
+}
+
+type WorkerInit<S> = Arc<dyn Fn(usize) -> LocalBoxFuture<'static, S> + Send + Sync>;
+
+type WorkerTeardown<S> = Arc<dyn Fn(Rc<S>) -> LocalBoxFuture<'static, ()> + Send + Sync>;
+
+/// A thread-per-core server: every worker thread runs its own compio runtime, its own
+/// `SO_REUSEPORT` listener and its own [`Router`], so nothing is shared between cores.
+///
+/// Workers talk to each other through the [`Mesh`] in their router state.
+///
+/// ```ignore
+/// Server::bind(addr)
+///     .workers(4)
+///     .on_worker_start(async |worker| Pool::connect(worker).await)
+///     .on_worker_stop(async |pool: Rc<Pool>| pool.close().await)
+///     .serve(|| Router::new().at("/", get(root)));
+/// ```
+pub struct Server<S = ()> {
+    addr: SocketAddr,
+    workers: usize,
+    init: WorkerInit<S>,
+    teardown: Option<WorkerTeardown<S>>,
+    handlers: MessageHandlers<S>,
+    shutdown: LocalBoxFuture<'static, ()>,
+}
+
+impl Server {
+    /// Create a server listening on `addr` with one worker per available CPU, which shuts
+    /// down on `Ctrl-C`.
+    pub fn bind(addr: SocketAddr) -> Self {
+        Self {
+            addr,
+            workers: thread::available_parallelism().map_or(1, |n| n.get()),
+            init: Arc::new(|_| Box::pin(async {})),
+            teardown: None,
+            handlers: Default::default(),
+            shutdown: Box::pin(async {
+                compio::signal::ctrl_c()
+                    .await
+                    .expect("cannot listen for Ctrl-C");
+            }),
+        }
+    }
+}
+
+impl<S: 'static> Server<S> {
+    /// Set the number of worker threads.
+    pub fn workers(mut self, workers: usize) -> Self {
+        assert!(workers > 0, "A server needs at least one worker");
+        self.workers = workers;
+        self
+    }
+
+    /// Run `init` on every worker's runtime before it starts accepting connections. The value
+    /// it resolves to becomes that worker's router state, see [`Router::with_state`].
+    ///
+    /// `init` receives the index of the worker, from `0` to `workers - 1`. Teardown and message
+    /// handlers set before are discarded, since they expect the previous state.
+    pub fn on_worker_start<T, F, Fut>(self, init: F) -> Server<T>
+    where
+        T: 'static,
+        F: Fn(usize) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = T> + 'static,
+    {
+        Server {
+            addr: self.addr,
+            workers: self.workers,
+            init: Arc::new(move |worker| Box::pin(init(worker))),
+            teardown: None,
+            handlers: Default::default(),
+            shutdown: self.shutdown,
+        }
+    }
+
+    /// Run `teardown` on every worker's runtime once it has stopped serving, with the state
+    /// produced by [`Server::on_worker_start`].
+    pub fn on_worker_stop<F, Fut>(mut self, teardown: F) -> Self
+    where
+        F: Fn(Rc<S>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + 'static,
+    {
+        self.teardown = Some(Arc::new(move |state| Box::pin(teardown(state))));
+        self
+    }
+
+    /// Handle the messages of type `M` sent through the [`Mesh`] on the receiving worker,
+    /// together with its state. The handler's output is the reply to [`Mesh::request`].
+    ///
+    /// A later handler for the same message type replaces the earlier one.
+    pub fn on_message<M, R, F, Fut>(mut self, handler: F) -> Self
+    where
+        M: Send + 'static,
+        R: Send + 'static,
+        F: Fn(M, Rc<S>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = R> + 'static,
+    {
+        self.handlers
+            .insert(TypeId::of::<M>(), message_handler(handler));
+        self
+    }
+
+    /// Shut the server down when `signal` resolves instead of on `Ctrl-C`.
+    pub fn shutdown_on(mut self, signal: impl Future<Output = ()> + 'static) -> Self {
+        self.shutdown = Box::pin(signal);
+        self
+    }
+
+    /// Start the workers, each serving the router built by `make_router`, and block until
+    /// the shutdown signal fires or a worker stops unexpectedly.
+    pub fn serve<F>(self, make_router: F)
+    where
+        F: Fn() -> Router + Send + Sync + 'static,
+    {
+        let make_router = Arc::new(make_router);
+        let handlers = Arc::new(self.handlers);
+        let (exited_tx, mut exited_rx) = mpsc::unbounded::<usize>();
+        let mut stops = Vec::with_capacity(self.workers);
+        let mut handles = Vec::with_capacity(self.workers);
+
+        for (id, (mesh, inbox)) in mesh::mesh(self.workers).into_iter().enumerate() {
+            let (stop_tx, stop_rx) = oneshot::channel::<()>();
+            let worker = Worker {
+                id,
+                addr: self.addr,
+                init: self.init.clone(),
+                teardown: self.teardown.clone(),
+                handlers: handlers.clone(),
+                make_router: make_router.clone(),
+                mesh,
+                inbox,
+                _exited: ExitGuard(id, exited_tx.clone()),
+            };
+            let handle = thread::Builder::new()
+                .name(format!("monet-worker-{id}"))
+                .spawn(move || worker.run(stop_rx))
+                .expect("cannot spawn worker thread");
+
+            stops.push(stop_tx);
+            handles.push(handle);
+        }
+
+        let rt = compio::runtime::Runtime::new().expect("cannot create runtime");
+        rt.block_on(future::select(self.shutdown, exited_rx.next()));
+
+        stops.into_iter().for_each(|stop| {
+            let _ = stop.send(());
+        });
+        for handle in handles {
+            if let Err(panic) = handle.join() {
+                std::panic::resume_unwind(panic);
+            }
+        }
+    }
+}
+
+struct Worker<S, F> {
+    id: usize,
+    addr: SocketAddr,
+    init: WorkerInit<S>,
+    teardown: Option<WorkerTeardown<S>>,
+    handlers: Arc<MessageHandlers<S>>,
+    make_router: Arc<F>,
+    mesh: Mesh,
+    inbox: mpsc::UnboundedReceiver<Envelope>,
+    _exited: ExitGuard,
+}
+
+impl<S, F> Worker<S, F>
+where
+    S: 'static,
+    F: Fn() -> Router,
+{
+    fn run(self, stop: oneshot::Receiver<()>) {
+        let rt = compio::runtime::Runtime::new().expect("cannot create runtime");
+        rt.block_on(async move {
+            let state = Rc::new((self.init)(self.id).await);
+
+            let mut router = (self.make_router)().with_state(self.mesh);
+            router.app_state.insert_rc(state.clone());
+            // Every worker checks its own router, the failure of one stops them all.
+            assert_state_provided(&router);
+
+            let opts = SocketOpts::new().reuse_address(true).reuse_port(true);
+            let listener = TcpListener::bind_with_options(self.addr, &opts)
+                .await
+                .unwrap_or_else(|err| {
+                    panic!("Worker {} cannot bind {}: {err}", self.id, self.addr)
+                });
+
+            let messages = handle_messages(self.inbox, self.handlers, state.clone());
+            future::select(
+                pin!(serve_connections(listener, &router, stop)),
+                pin!(messages),
+            )
+            .await;
+            drop(router);
+
+            if let Some(teardown) = &self.teardown {
+                teardown(state).await;
+            }
+        });
+    }
+}
+
+/// Tells the server a worker stopped, whether it returned or panicked.
+struct ExitGuard(usize, mpsc::UnboundedSender<usize>);
+
+impl Drop for ExitGuard {
+    fn drop(&mut self) {
+        let _ = self.1.unbounded_send(self.0);
+    }
+}
+
 /// Types that can listen for connections.
 pub trait Listener: 'static {
     /// The listener's IO type.
@@ -0,0 +1,206 @@
+use std::{
+    any::{Any, TypeId, type_name},
+    collections::HashMap,
+    future::Future,
+    rc::Rc,
+    sync::Arc,
+};
+
+use futures::{
+    StreamExt,
+    channel::{mpsc, oneshot},
+    future::LocalBoxFuture,
+};
+use http::StatusCode;
+use thiserror::Error as ThisError;
+
+use crate::response::{IntoResponse, Response};
+
+type AnyMessage = Box<dyn Any + Send>;
+
+pub(crate) type MessageHandler<S> =
+    Arc<dyn Fn(AnyMessage, Rc<S>) -> LocalBoxFuture<'static, AnyMessage> + Send + Sync>;
+
+pub(crate) type MessageHandlers<S> = HashMap<TypeId, MessageHandler<S>>;
+
+pub(crate) fn message_handler<S, M, R, F, Fut>(handler: F) -> MessageHandler<S>
+where
+    S: 'static,
+    M: Send + 'static,
+    R: Send + 'static,
+    F: Fn(M, Rc<S>) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = R> + 'static,
+{
+    Arc::new(move |msg, state| {
+        let msg = *msg.downcast::<M>().expect(crate::GUARANTEE);
+        let reply = handler(msg, state);
+        Box::pin(async move { Box::new(reply.await) as AnyMessage })
+    })
+}
+
+pub(crate) struct Envelope {
+    type_id: TypeId,
+    #[cfg_attr(feature = "no-tracing", allow(dead_code))]
+    type_name: &'static str,
+    msg: AnyMessage,
+    reply: Option<oneshot::Sender<AnyMessage>>,
+}
+
+#[derive(ThisError, Debug)]
+pub enum MeshError {
+    #[error("There is no worker {0}")]
+    UnknownWorker(usize),
+
+    #[error("Worker {0} has stopped")]
+    WorkerStopped(usize),
+
+    #[error("Worker {worker} did not reply to a `{message}` message, is a handler registered?")]
+    NoReply {
+        worker: usize,
+        message: &'static str,
+    },
+
+    #[error("Worker {worker} replied to a `{message}` message with another type than `{expected}`")]
+    ReplyTypeMismatch {
+        worker: usize,
+        message: &'static str,
+        expected: &'static str,
+    },
+}
+
+impl IntoResponse for MeshError {
+    fn into_response(self) -> Response {
+        (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()).into_response()
+    }
+}
+
+/// A handle to the channels between the workers of a [`Server`].
+///
+/// Every worker's router gets one as application state, so handlers reach it with
+/// `req.app_state::<Mesh>()`. Messages are handled on the receiving worker's runtime by the
+/// handler registered for their type with [`Server::on_message`], together with that worker's
+/// state. This lets cores that share nothing tell each other to e.g. drop a cache entry.
+///
+/// [`Server`]: crate::Server
+/// [`Server::on_message`]: crate::Server::on_message
+#[derive(Clone, Debug)]
+pub struct Mesh {
+    id: usize,
+    inboxes: Arc<[mpsc::UnboundedSender<Envelope>]>,
+}
+
+impl Mesh {
+    /// The index of the worker this handle belongs to.
+    pub fn worker_id(&self) -> usize {
+        self.id
+    }
+
+    /// The number of workers in the server.
+    pub fn workers(&self) -> usize {
+        self.inboxes.len()
+    }
+
+    /// Send `msg` to `worker` without waiting for it to be handled.
+    pub fn send<M: Send + 'static>(&self, worker: usize, msg: M) -> Result<(), MeshError> {
+        self.deliver(
+            worker,
+            Box::new(msg),
+            type_name::<M>(),
+            TypeId::of::<M>(),
+            None,
+        )
+    }
+
+    /// Send a copy of `msg` to every worker, the current one included.
+    pub fn broadcast<M: Clone + Send + 'static>(&self, msg: M) -> Result<(), MeshError> {
+        (0..self.workers()).try_for_each(|worker| self.send(worker, msg.clone()))
+    }
+
+    /// Send `msg` to `worker` and wait for the value its handler returns.
+    pub async fn request<M, R>(&self, worker: usize, msg: M) -> Result<R, MeshError>
+    where
+        M: Send + 'static,
+        R: Send + 'static,
+    {
+        let (tx, rx) = oneshot::channel();
+        let message = type_name::<M>();
+        self.deliver(worker, Box::new(msg), message, TypeId::of::<M>(), Some(tx))?;
+
+        let reply = rx
+            .await
+            .map_err(|_| MeshError::NoReply { worker, message })?;
+        reply
+            .downcast::<R>()
+            .map(|reply| *reply)
+            .map_err(|_| MeshError::ReplyTypeMismatch {
+                worker,
+                message,
+                expected: type_name::<R>(),
+            })
+    }
+
+    fn deliver(
+        &self,
+        worker: usize,
+        msg: AnyMessage,
+        type_name: &'static str,
+        type_id: TypeId,
+        reply: Option<oneshot::Sender<AnyMessage>>,
+    ) -> Result<(), MeshError> {
+        let inbox = self
+            .inboxes
+            .get(worker)
+            .ok_or(MeshError::UnknownWorker(worker))?;
+        let envelope = Envelope {
+            type_id,
+            type_name,
+            msg,
+            reply,
+        };
+        inbox
+            .unbounded_send(envelope)
+            .map_err(|_| MeshError::WorkerStopped(worker))
+    }
+}
+
+/// Create the handles and inboxes of `workers` workers, indexed by worker.
+pub(crate) fn mesh(workers: usize) -> Vec<(Mesh, mpsc::UnboundedReceiver<Envelope>)> {
+    let (senders, receivers): (Vec<_>, Vec<_>) = (0..workers).map(|_| mpsc::unbounded()).unzip();
+    let inboxes: Arc<[_]> = senders.into();
+
+    receivers
+        .into_iter()
+        .enumerate()
+        .map(|(id, rx)| {
+            let handle = Mesh {
+                id,
+                inboxes: inboxes.clone(),
+            };
+            (handle, rx)
+        })
+        .collect()
+}
+
+/// Handle the messages sent to a worker, each on its own task, until every handle is dropped.
+pub(crate) async fn handle_messages<S: 'static>(
+    mut inbox: mpsc::UnboundedReceiver<Envelope>,
+    handlers: Arc<MessageHandlers<S>>,
+    state: Rc<S>,
+) {
+    while let Some(envelope) = inbox.next().await {
+        let Some(handler) = handlers.get(&envelope.type_id) else {
+            #[cfg(not(feature = "no-tracing"))]
+            tracing::warn!(message = envelope.type_name, "no handler for message");
+            continue;
+        };
+
+        let reply = handler(envelope.msg, state.clone());
+        compio::runtime::spawn(async move {
+            let reply = reply.await;
+            if let Some(tx) = envelope.reply {
+                let _ = tx.send(reply);
+            }
+        })
+        .detach();
+    }
+}
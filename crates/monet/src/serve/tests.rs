@@ -0,0 +1,190 @@
+use std::{
+    cell::Cell,
+    io::{Read, Write},
+    net::{SocketAddr, TcpStream},
+    rc::Rc,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    thread,
+    time::Duration,
+};
+
+use futures::channel::oneshot;
+
+use crate::{
+    Error, Mesh, Request, Router, Server, get,
+    serve::mesh::{self, MeshError},
+};
+
+struct WorkerState(usize);
+
+async fn worker_id(req: Request) -> String {
+    req.app_state::<WorkerState>().unwrap().0.to_string()
+}
+
+fn free_addr() -> SocketAddr {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    listener.local_addr().unwrap()
+}
+
+fn get_body(addr: SocketAddr, path: &str) -> String {
+    for _ in 0..50 {
+        if let Ok(mut stream) = TcpStream::connect(addr) {
+            write!(
+                stream,
+                "GET {path} HTTP/1.1\r\nHost: test\r\nConnection: close\r\n\r\n"
+            )
+            .unwrap();
+            let mut resp = String::new();
+            stream.read_to_string(&mut resp).unwrap();
+            return resp
+                .split("\r\n\r\n")
+                .nth(1)
+                .unwrap_or_default()
+                .to_string();
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+    panic!("server never started listening on {addr}");
+}
+
+#[test]
+fn per_worker_state() {
+    let addr = free_addr();
+    let started = Arc::new(AtomicUsize::new(0));
+    let stopped = Arc::new(AtomicUsize::new(0));
+    let (stop_tx, stop_rx) = oneshot::channel::<()>();
+
+    let server = {
+        let started = started.clone();
+        let stopped = stopped.clone();
+        thread::spawn(move || {
+            Server::bind(addr)
+                .workers(2)
+                .on_worker_start(move |id| {
+                    started.fetch_add(1, Ordering::SeqCst);
+                    async move { WorkerState(id) }
+                })
+                .on_worker_stop(move |state: Rc<WorkerState>| {
+                    assert!(state.0 < 2);
+                    stopped.fetch_add(1, Ordering::SeqCst);
+                    async {}
+                })
+                .shutdown_on(async {
+                    let _ = stop_rx.await;
+                })
+                .serve(|| Router::new().at("/", get(worker_id)));
+        })
+    };
+
+    let id: usize = get_body(addr, "/").parse().unwrap();
+    assert!(id < 2);
+
+    stop_tx.send(()).unwrap();
+    server.join().unwrap();
+    assert_eq!(started.load(Ordering::SeqCst), 2);
+    assert_eq!(stopped.load(Ordering::SeqCst), 2);
+}
+
+#[derive(Clone)]
+struct Bump;
+
+struct Count;
+
+async fn bump(req: Request) -> Result<(), Error> {
+    req.app_state::<Mesh>()?.broadcast(Bump).unwrap();
+    Ok(())
+}
+
+async fn sum(req: Request) -> Result<String, MeshError> {
+    let mesh = req.app_state::<Mesh>().unwrap();
+    let mut sum = 0;
+    for worker in 0..mesh.workers() {
+        sum += mesh.request::<_, u32>(worker, Count).await?;
+    }
+    Ok(sum.to_string())
+}
+
+#[test]
+fn cross_worker_messages() {
+    let addr = free_addr();
+    let (stop_tx, stop_rx) = oneshot::channel::<()>();
+
+    let server = thread::spawn(move || {
+        Server::bind(addr)
+            .workers(3)
+            .on_worker_start(async |_| Cell::new(0u32))
+            .on_message(async |Bump, count: Rc<Cell<u32>>| count.set(count.get() + 1))
+            .on_message(async |Count, count: Rc<Cell<u32>>| count.get())
+            .shutdown_on(async {
+                let _ = stop_rx.await;
+            })
+            .serve(|| Router::new().at("/bump", get(bump)).at("/sum", get(sum)));
+    });
+
+    get_body(addr, "/bump");
+    let mut total = String::new();
+    for _ in 0..50 {
+        total = get_body(addr, "/sum");
+        if total == "3" {
+            break;
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+    assert_eq!(total, "3");
+
+    stop_tx.send(()).unwrap();
+    server.join().unwrap();
+}
+
+#[compio::test]
+async fn reply_type_mismatch() {
+    let (handle, inbox) = mesh::mesh(1).pop().unwrap();
+    let handlers = [(
+        std::any::TypeId::of::<Count>(),
+        mesh::message_handler(async |Count, _: Rc<()>| 1u32),
+    )];
+    compio::runtime::spawn(mesh::handle_messages(
+        inbox,
+        Arc::new(handlers.into_iter().collect()),
+        Rc::new(()),
+    ))
+    .detach();
+
+    assert_eq!(handle.request::<_, u32>(0, Count).await.unwrap(), 1);
+    let err = handle.request::<_, String>(0, Count).await.unwrap_err();
+    assert!(matches!(
+        err,
+        MeshError::ReplyTypeMismatch {
+            worker: 0,
+            expected: "alloc::string::String",
+            ..
+        }
+    ));
+}
+
+#[test]
+#[should_panic(expected = "never provided with `Router::with_state`")]
+fn missing_worker_state() {
+    use crate::request::StateKey;
+
+    struct NeedsState;
+
+    #[async_trait::async_trait(?Send)]
+    impl crate::Endpoint for NeedsState {
+        async fn call(&self, req: Request) -> crate::Response {
+            use crate::IntoResponse;
+            worker_id(req).await.into_response()
+        }
+
+        fn required_state(&self) -> Vec<StateKey> {
+            vec![StateKey::of::<WorkerState>()]
+        }
+    }
+
+    Server::bind(free_addr())
+        .workers(1)
+        .serve(|| Router::new().at("/", get(NeedsState)));
+}
@@ -1,4 +1,29 @@
-use http::{HeaderMap, header};
+mod content_type;
+#[cfg(feature = "ndjson")]
+mod ndjson;
+#[cfg(test)]
+mod tests;
+pub(crate) mod typed_header;
+
+use std::rc::Rc;
+
+#[cfg(feature = "cbor")]
+pub use self::content_type::CborConfig;
+#[cfg(feature = "msgpack")]
+pub use self::content_type::MsgPackConfig;
+#[cfg(feature = "ndjson")]
+pub use self::{content_type::NdJsonConfig, ndjson::NdJsonStream};
+pub use self::{
+    content_type::{ContentTypes, FormConfig, JsonConfig},
+    typed_header::TypedHeader,
+};
+
+#[cfg(feature = "msgpack")]
+pub const APPLICATION_MSGPACK: &str = "application/msgpack";
+#[cfg(feature = "cbor")]
+pub const APPLICATION_CBOR: &str = "application/cbor";
+#[cfg(feature = "ndjson")]
+pub const APPLICATION_NDJSON: &str = "application/x-ndjson";
 
 #[derive(Debug, Clone, Copy, Default, serde::Deserialize)]
 pub struct Form<T>(pub T);
@@ -35,6 +60,78 @@ impl<T> std::ops::DerefMut for Json<T> {
     }
 }
 
+/// A MessagePack body. Structs are serialized as maps, with their field names.
+#[cfg(feature = "msgpack")]
+#[derive(Debug, Clone, Copy, Default)]
+#[must_use]
+pub struct MsgPack<T>(pub T);
+
+#[cfg(feature = "msgpack")]
+impl<T> std::ops::Deref for MsgPack<T> {
+    type Target = T;
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+#[cfg(feature = "msgpack")]
+impl<T> std::ops::DerefMut for MsgPack<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+/// A CBOR body.
+#[cfg(feature = "cbor")]
+#[derive(Debug, Clone, Copy, Default)]
+#[must_use]
+pub struct Cbor<T>(pub T);
+
+#[cfg(feature = "cbor")]
+impl<T> std::ops::Deref for Cbor<T> {
+    type Target = T;
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+#[cfg(feature = "cbor")]
+impl<T> std::ops::DerefMut for Cbor<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+/// A newline-delimited JSON body, one value per line, read and written incrementally.
+///
+/// As a response, `S` is a [`Stream`] of values sent as they are produced. As an extractor,
+/// `NdJson<NdJsonStream<T>>` yields the values of the request as their lines arrive.
+///
+/// ```ignore
+/// async fn ingest(NdJson(mut lines): NdJson<NdJsonStream<LogLine>>) -> Result<String, Error> {
+///     let mut count = 0;
+///     while let Some(line) = lines.next().await {
+///         store(line?);
+///         count += 1;
+///     }
+///     Ok(format!("{count} lines"))
+/// }
+///
+/// async fn export() -> NdJson<impl Stream<Item = LogLine>> {
+///     NdJson(futures::stream::iter(recent_lines()))
+/// }
+/// ```
+///
+/// [`Stream`]: futures::Stream
+#[cfg(feature = "ndjson")]
+#[derive(Debug, Clone, Copy, Default)]
+#[must_use]
+pub struct NdJson<S>(pub S);
+
+/// The query string, deserialized with repeated keys collected into sequences, e.g.
+/// `?tag=a&tag=b` into `tags: Vec<String>` with `#[serde(rename = "tag")]`.
 #[derive(Debug, Clone, Copy, Default)]
 pub struct Query<T>(pub T);
 
@@ -52,22 +149,32 @@ impl<T> std::ops::DerefMut for Query<T> {
     }
 }
 
+/// The query string, deserialized with bracket-style nesting: `filter[status]=open` into a
+/// nested struct and `ids[]=1&ids[]=2` or `ids[0]=1&ids[1]=2` into a sequence.
+#[cfg(feature = "nested-query")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NestedQuery<T>(pub T);
+
+#[cfg(feature = "nested-query")]
+impl<T> std::ops::Deref for NestedQuery<T> {
+    type Target = T;
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+#[cfg(feature = "nested-query")]
+impl<T> std::ops::DerefMut for NestedQuery<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 #[must_use]
 pub struct Html<T>(pub T);
 
-pub(super) fn has_content_type(headers: &HeaderMap, expected_content_type: &mime::Mime) -> bool {
-    let Some(content_type) = headers.get(header::CONTENT_TYPE) else {
-        return false;
-    };
-
-    let Ok(content_type) = content_type.to_str() else {
-        return false;
-    };
-
-    content_type == expected_content_type.as_ref()
-}
-
 impl<T> std::ops::Deref for Html<T> {
     type Target = T;
     #[inline]
@@ -98,3 +205,23 @@ impl<T> std::ops::DerefMut for Path<T> {
         &mut self.0
     }
 }
+
+/// Application state added with [`Router::with_state`], extracted by type.
+///
+/// [`Router::with_state`]: crate::Router::with_state
+#[derive(Debug)]
+pub struct AppState<T>(pub Rc<T>);
+
+impl<T> Clone for AppState<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T> std::ops::Deref for AppState<T> {
+    type Target = T;
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
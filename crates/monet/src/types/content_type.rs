@@ -0,0 +1,211 @@
+use async_trait::async_trait;
+use http::{HeaderMap, header};
+use mime::Mime;
+
+use crate::{Layer, Middleware, Request, Response};
+
+/// The media types a body extractor accepts in the request's `Content-Type`.
+///
+/// Types and subtypes are compared case-insensitively and parameters such as `charset` are
+/// ignored, so `Application/JSON; charset=utf-8` matches `application/json`.
+#[derive(Clone, Debug, Default)]
+pub struct ContentTypes {
+    types: Vec<Mime>,
+    suffixes: Vec<String>,
+}
+
+impl ContentTypes {
+    /// Accept nothing, to be extended with [`ContentTypes::with`].
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// `application/json` and any `+json` type, e.g. `application/problem+json`.
+    pub fn json() -> Self {
+        Self::new().with(mime::APPLICATION_JSON).with_suffix("json")
+    }
+
+    /// `application/x-www-form-urlencoded`.
+    pub fn form() -> Self {
+        Self::new().with(mime::APPLICATION_WWW_FORM_URLENCODED)
+    }
+
+    /// `application/msgpack`, and the unregistered `application/x-msgpack` and
+    /// `application/vnd.msgpack`.
+    #[cfg(feature = "msgpack")]
+    pub fn msgpack() -> Self {
+        Self::new()
+            .with(super::APPLICATION_MSGPACK.parse().expect(crate::GUARANTEE))
+            .with("application/x-msgpack".parse().expect(crate::GUARANTEE))
+            .with("application/vnd.msgpack".parse().expect(crate::GUARANTEE))
+    }
+
+    /// `application/cbor` and any `+cbor` type.
+    #[cfg(feature = "cbor")]
+    pub fn cbor() -> Self {
+        Self::new()
+            .with(super::APPLICATION_CBOR.parse().expect(crate::GUARANTEE))
+            .with_suffix("cbor")
+    }
+
+    /// `application/x-ndjson`, and `application/jsonl` used for JSON Lines.
+    #[cfg(feature = "ndjson")]
+    pub fn ndjson() -> Self {
+        Self::new()
+            .with(super::APPLICATION_NDJSON.parse().expect(crate::GUARANTEE))
+            .with("application/jsonl".parse().expect(crate::GUARANTEE))
+    }
+
+    /// Accept `media_type` too. A `*` subtype, as in `text/*`, accepts every subtype.
+    pub fn with(mut self, media_type: Mime) -> Self {
+        self.types.push(media_type);
+        self
+    }
+
+    /// Accept every type with the `+suffix` structured syntax suffix, e.g. `xml`.
+    pub fn with_suffix(mut self, suffix: &str) -> Self {
+        self.suffixes.push(suffix.to_string());
+        self
+    }
+
+    /// Whether `media_type` is accepted.
+    pub fn matches(&self, media_type: &Mime) -> bool {
+        let eq = |a: &str, b: &str| a.eq_ignore_ascii_case(b);
+        let by_type = self.types.iter().any(|accepted| {
+            (accepted.type_() == mime::STAR
+                || eq(accepted.type_().as_str(), media_type.type_().as_str()))
+                && (accepted.subtype() == mime::STAR
+                    || eq(accepted.subtype().as_str(), media_type.subtype().as_str()))
+        });
+        let by_suffix = media_type.suffix().is_some_and(|suffix| {
+            self.suffixes
+                .iter()
+                .any(|accepted| eq(accepted, suffix.as_str()))
+        });
+        by_type || by_suffix
+    }
+
+    /// Whether the `Content-Type` of `headers` is accepted. A missing or malformed header
+    /// never is.
+    pub fn matches_headers(&self, headers: &HeaderMap) -> bool {
+        headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<Mime>().ok())
+            .is_some_and(|media_type| self.matches(&media_type))
+    }
+}
+
+/// Configures the [`Json`](crate::Json) extractor, for the whole router when provided as
+/// application state, or for some routes when wrapping them as a middleware:
+///
+/// ```ignore
+/// let legacy = JsonConfig {
+///     content_types: ContentTypes::json().with(mime::TEXT_PLAIN),
+/// };
+/// let app = Router::new()
+///     .at("/legacy/import", post(import).wrap(legacy))
+///     .at("/import", post(import));
+/// ```
+///
+/// The innermost middleware wins over the application state.
+#[derive(Clone, Debug)]
+pub struct JsonConfig {
+    pub content_types: ContentTypes,
+}
+
+impl Default for JsonConfig {
+    fn default() -> Self {
+        Self {
+            content_types: ContentTypes::json(),
+        }
+    }
+}
+
+/// Configures the [`Form`](crate::Form) extractor, like [`JsonConfig`] does for `Json`.
+#[derive(Clone, Debug)]
+pub struct FormConfig {
+    pub content_types: ContentTypes,
+}
+
+impl Default for FormConfig {
+    fn default() -> Self {
+        Self {
+            content_types: ContentTypes::form(),
+        }
+    }
+}
+
+/// Configures the [`MsgPack`](crate::MsgPack) extractor, like [`JsonConfig`] does for `Json`.
+#[cfg(feature = "msgpack")]
+#[derive(Clone, Debug)]
+pub struct MsgPackConfig {
+    pub content_types: ContentTypes,
+}
+
+#[cfg(feature = "msgpack")]
+impl Default for MsgPackConfig {
+    fn default() -> Self {
+        Self {
+            content_types: ContentTypes::msgpack(),
+        }
+    }
+}
+
+/// Configures the [`Cbor`](crate::Cbor) extractor, like [`JsonConfig`] does for `Json`.
+#[cfg(feature = "cbor")]
+#[derive(Clone, Debug)]
+pub struct CborConfig {
+    pub content_types: ContentTypes,
+}
+
+#[cfg(feature = "cbor")]
+impl Default for CborConfig {
+    fn default() -> Self {
+        Self {
+            content_types: ContentTypes::cbor(),
+        }
+    }
+}
+
+/// Configures the [`NdJson`](crate::NdJson) extractor, like [`JsonConfig`] does for `Json`.
+#[cfg(feature = "ndjson")]
+#[derive(Clone, Debug)]
+pub struct NdJsonConfig {
+    pub content_types: ContentTypes,
+}
+
+#[cfg(feature = "ndjson")]
+impl Default for NdJsonConfig {
+    fn default() -> Self {
+        Self {
+            content_types: ContentTypes::ndjson(),
+        }
+    }
+}
+
+macro_rules! impl_config_middleware {
+    ($($(#[$cfg:meta])* $config:ty),*) => {
+        $(
+            $(#[$cfg])*
+            #[async_trait(?Send)]
+            impl Middleware for $config {
+                async fn transform(&self, mut req: Request, layer: Layer) -> Response {
+                    req.extensions_mut().insert(self.clone());
+                    layer.next(req).await
+                }
+            }
+        )*
+    };
+}
+
+impl_config_middleware!(
+    JsonConfig,
+    FormConfig,
+    #[cfg(feature = "msgpack")]
+    MsgPackConfig,
+    #[cfg(feature = "cbor")]
+    CborConfig,
+    #[cfg(feature = "ndjson")]
+    NdJsonConfig
+);
@@ -0,0 +1,107 @@
+use std::{
+    fmt,
+    marker::PhantomData,
+    pin::Pin,
+    task::{Context, Poll, ready},
+};
+
+use bytes::{Buf, BytesMut};
+use futures::Stream;
+use http_body::Body as _;
+use serde::de::DeserializeOwned;
+
+use crate::{body::Body, error::Error};
+
+/// The values of a newline-delimited JSON request body, parsed line by line as the body
+/// arrives. Blank lines are skipped.
+///
+/// A line that fails to parse yields an error and the stream goes on with the next one, while
+/// a failure to read the body, e.g. crossing its size limit, ends the stream.
+pub struct NdJsonStream<T> {
+    body: Body,
+    buf: BytesMut,
+    limit: usize,
+    done: bool,
+    _value: PhantomData<fn() -> T>,
+}
+
+impl<T> NdJsonStream<T> {
+    pub(crate) fn new(body: Body, limit: Option<usize>) -> Self {
+        Self {
+            body,
+            buf: BytesMut::new(),
+            limit: limit.unwrap_or(usize::MAX),
+            done: false,
+            _value: PhantomData,
+        }
+    }
+}
+
+impl<T: DeserializeOwned> NdJsonStream<T> {
+    /// Parse the next complete line of the buffer, or the rest of it once the body is over.
+    fn next_line(&mut self) -> Option<Result<T, Error>> {
+        loop {
+            let line = match self.buf.iter().position(|b| *b == b'\n') {
+                Some(end) => {
+                    let line = self.buf.split_to(end);
+                    self.buf.advance(1);
+                    line
+                }
+                None if self.done => self.buf.split(),
+                None => return None,
+            };
+            if line.iter().all(u8::is_ascii_whitespace) {
+                if self.buf.is_empty() && self.done {
+                    return None;
+                }
+                continue;
+            }
+            return Some(parse(&line));
+        }
+    }
+}
+
+fn parse<T: DeserializeOwned>(line: &[u8]) -> Result<T, Error> {
+    let mut deserializer = serde_json::Deserializer::from_slice(line);
+    let value = serde_path_to_error::deserialize(&mut deserializer)?;
+    deserializer.end()?;
+    Ok(value)
+}
+
+impl<T: DeserializeOwned> Stream for NdJsonStream<T> {
+    type Item = Result<T, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        loop {
+            if let Some(value) = this.next_line() {
+                return Poll::Ready(Some(value));
+            }
+            if this.done {
+                return Poll::Ready(None);
+            }
+            match ready!(Pin::new(&mut this.body).poll_frame(cx)) {
+                Some(Ok(frame)) => {
+                    if let Ok(data) = frame.into_data() {
+                        this.buf.extend_from_slice(&data);
+                    }
+                }
+                Some(Err(err)) => {
+                    this.done = true;
+                    this.buf.clear();
+                    return Poll::Ready(Some(Err(match err.is_length_limit() {
+                        true => Error::PayloadTooLarge { limit: this.limit },
+                        false => err.into(),
+                    })));
+                }
+                None => this.done = true,
+            }
+        }
+    }
+}
+
+impl<T> fmt::Debug for NdJsonStream<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NdJsonStream").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,328 @@
+use http::{HeaderValue, Method, StatusCode, header};
+use mime::Mime;
+
+use crate::{
+    IntoResponse, Router, TypedHeader, get,
+    headers::{Accept, Authorization, ETag, Header, UserAgent, authorization::Bearer},
+    router::tests::request,
+};
+
+fn accept(value: &'static str) -> Accept {
+    Accept::decode(&mut [HeaderValue::from_static(value)].iter()).unwrap()
+}
+
+#[test]
+fn accept_quality() {
+    let accept = accept("text/*;q=0.5, application/json, */*;q=0.1");
+    let json: Mime = "application/json".parse().unwrap();
+    let html: Mime = "text/html".parse().unwrap();
+    let png: Mime = "image/png".parse().unwrap();
+
+    assert_eq!(accept.iter().next().unwrap().as_ref(), "application/json");
+    assert_eq!(accept.quality(&json), 1.0);
+    assert_eq!(accept.quality(&html), 0.5);
+    assert_eq!(accept.quality(&png), 0.1);
+    assert_eq!(accept.preferred(&[html.clone(), json.clone()]), Some(&json));
+
+    let accept = self::accept("text/html;q=0");
+    assert!(!accept.accepts(&html));
+    assert_eq!(accept.preferred(&[html]), None);
+
+    let invalid = HeaderValue::from_static("text/html;q=2");
+    assert!(Accept::decode(&mut [invalid].iter()).is_err());
+}
+
+#[compio::test]
+async fn typed_header_extractor() {
+    async fn whoami(
+        TypedHeader(auth): TypedHeader<Authorization<Bearer>>,
+        agent: Option<TypedHeader<UserAgent>>,
+    ) -> String {
+        format!(
+            "{} {}",
+            auth.token(),
+            agent.as_ref().map_or("unknown", |agent| agent.as_str())
+        )
+    }
+
+    let app = Router::new().at("/", get(whoami));
+    let call = async |auth: Option<&'static str>| {
+        let mut req = request(Method::GET, "/");
+        if let Some(auth) = auth {
+            req.headers_mut()
+                .insert(header::AUTHORIZATION, HeaderValue::from_static(auth));
+        }
+        app.handle(req).await.status()
+    };
+
+    assert_eq!(call(Some("Bearer abc")).await, StatusCode::OK);
+    assert_eq!(call(Some("Basic abc")).await, StatusCode::BAD_REQUEST);
+    assert_eq!(call(None).await, StatusCode::BAD_REQUEST);
+}
+
+#[test]
+fn typed_header_response() {
+    let etag: ETag = "\"v1\"".parse().unwrap();
+    let resp = (TypedHeader(etag), "body").into_response();
+    assert_eq!(resp.headers()[header::ETAG], "\"v1\"");
+}
+
+#[test]
+fn content_type_matching() {
+    use crate::types::ContentTypes;
+
+    let json = ContentTypes::json();
+    let matches = |types: &ContentTypes, value: &str| types.matches(&value.parse().unwrap());
+
+    assert!(matches(&json, "application/json"));
+    assert!(matches(&json, "application/json; charset=utf-8"));
+    assert!(matches(&json, "Application/JSON"));
+    assert!(matches(&json, "application/problem+json"));
+    assert!(!matches(&json, "text/plain"));
+    assert!(!matches(&json, "application/jsonx"));
+
+    let text = ContentTypes::new().with(mime::TEXT_STAR);
+    assert!(matches(&text, "text/csv"));
+    assert!(!matches(&text, "application/csv"));
+}
+
+#[compio::test]
+async fn json_config() {
+    use http_body_util::Full;
+
+    use crate::{
+        Json,
+        body::Body,
+        post,
+        types::{ContentTypes, JsonConfig},
+    };
+
+    async fn echo(Json(value): Json<serde_json::Value>) -> Json<serde_json::Value> {
+        Json(value)
+    }
+
+    let status = async |app: &Router, content_type| {
+        let mut req = request(Method::POST, "/");
+        req.headers_mut()
+            .insert(header::CONTENT_TYPE, HeaderValue::from_static(content_type));
+        req.body = Body::new(Full::new("{}".into()));
+        app.handle(req).await.status()
+    };
+
+    let app = Router::new().at("/", post(echo));
+    assert_eq!(
+        status(&app, "application/json; charset=utf-8").await,
+        StatusCode::OK
+    );
+    assert_eq!(
+        status(&app, "application/merge-patch+json").await,
+        StatusCode::OK
+    );
+    assert_eq!(
+        status(&app, "text/plain").await,
+        StatusCode::UNSUPPORTED_MEDIA_TYPE
+    );
+
+    let mut config = JsonConfig::default();
+    config.content_types = config.content_types.with(mime::TEXT_PLAIN);
+    let app = Router::new().at("/", post(echo)).with_state(config);
+    assert_eq!(status(&app, "text/plain").await, StatusCode::OK);
+
+    let only_text = JsonConfig {
+        content_types: ContentTypes::new().with(mime::TEXT_PLAIN),
+    };
+    let app = Router::new()
+        .at("/", post(echo).wrap(only_text))
+        .with_state(JsonConfig::default());
+    assert_eq!(status(&app, "text/plain").await, StatusCode::OK);
+    assert_eq!(
+        status(&app, "application/json").await,
+        StatusCode::UNSUPPORTED_MEDIA_TYPE
+    );
+}
+
+#[test]
+fn query_sequences() {
+    #[derive(serde::Deserialize)]
+    struct Search {
+        #[serde(rename = "tag", default)]
+        tags: Vec<String>,
+        page: Option<u32>,
+    }
+
+    let req = request(Method::GET, "/search?tag=a&page=2&tag=b%20c");
+    let search = req.query::<Search>().unwrap();
+    assert_eq!(search.tags, ["a", "b c"]);
+    assert_eq!(search.page, Some(2));
+
+    let req = request(Method::GET, "/search");
+    assert!(req.query::<Search>().unwrap().tags.is_empty());
+
+    let req = request(Method::GET, "/search?page=x");
+    let err = req.query::<Search>().err().unwrap();
+    assert_eq!(err.status_code(), StatusCode::BAD_REQUEST);
+    assert_eq!(err.problem().extensions["field"], "page");
+}
+
+#[cfg(feature = "nested-query")]
+#[test]
+fn nested_query() {
+    #[derive(serde::Deserialize)]
+    struct Filter {
+        status: String,
+        labels: Vec<String>,
+    }
+
+    #[derive(serde::Deserialize)]
+    struct Search {
+        filter: Filter,
+        ids: Vec<u32>,
+    }
+
+    let uri = "/issues?filter[status]=open&filter[labels][]=bug&filter[labels][]=ui\
+               &ids%5B0%5D=1&ids%5B1%5D=2";
+    let search = request(Method::GET, uri).nested_query::<Search>().unwrap();
+    assert_eq!(search.filter.status, "open");
+    assert_eq!(search.filter.labels, ["bug", "ui"]);
+    assert_eq!(search.ids, [1, 2]);
+
+    let err = request(Method::GET, "/issues?filter[status]=open&ids[]=x")
+        .nested_query::<Search>()
+        .err()
+        .unwrap();
+    assert_eq!(err.status_code(), StatusCode::BAD_REQUEST);
+}
+
+#[cfg(any(feature = "msgpack", feature = "cbor", feature = "ndjson"))]
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[cfg(any(feature = "msgpack", feature = "cbor", feature = "ndjson"))]
+async fn send(
+    app: &Router,
+    content_type: &'static str,
+    body: impl Into<bytes::Bytes>,
+) -> crate::Response {
+    use http_body_util::Full;
+
+    let mut req = request(Method::POST, "/");
+    req.headers_mut()
+        .insert(header::CONTENT_TYPE, HeaderValue::from_static(content_type));
+    req.body = crate::body::Body::new(Full::new(body.into()));
+    app.handle(req).await
+}
+
+#[cfg(any(feature = "msgpack", feature = "cbor", feature = "ndjson"))]
+async fn bytes(resp: crate::Response) -> bytes::Bytes {
+    use http_body_util::BodyExt;
+
+    resp.into_body().collect().await.unwrap().to_bytes()
+}
+
+#[cfg(feature = "msgpack")]
+#[compio::test]
+async fn msgpack() {
+    use crate::{MsgPack, post};
+
+    let app = Router::new().at(
+        "/",
+        post(async |MsgPack(p): MsgPack<Point>| MsgPack(Point { x: p.y, y: p.x })),
+    );
+    let body = rmp_serde::to_vec_named(&Point { x: 1, y: 2 }).unwrap();
+
+    let resp = send(&app, "application/msgpack", body.clone()).await;
+    assert_eq!(resp.headers()[header::CONTENT_TYPE], "application/msgpack");
+    let point: Point = rmp_serde::from_slice(&bytes(resp).await).unwrap();
+    assert_eq!(point, Point { x: 2, y: 1 });
+
+    let resp = send(&app, "application/json", body).await;
+    assert_eq!(resp.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+    let wrong_type = rmp_serde::to_vec_named(&(1, "two")).unwrap();
+    let resp = send(&app, "application/x-msgpack", wrong_type).await;
+    assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[cfg(feature = "msgpack")]
+#[compio::test]
+async fn msgpack_config() {
+    use crate::{
+        MsgPack, post,
+        types::{ContentTypes, MsgPackConfig},
+    };
+
+    let custom = MsgPackConfig {
+        content_types: ContentTypes::msgpack()
+            .with("application/vnd.acme+msgpack".parse().unwrap()),
+    };
+    let app = Router::new().at(
+        "/",
+        post(async |MsgPack(p): MsgPack<Point>| MsgPack(p)).wrap(custom),
+    );
+    let body = rmp_serde::to_vec_named(&Point { x: 1, y: 2 }).unwrap();
+
+    let resp = send(&app, "application/vnd.acme+msgpack", body.clone()).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let resp = send(&app, "application/octet-stream", body).await;
+    assert_eq!(resp.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+}
+
+#[cfg(feature = "cbor")]
+#[compio::test]
+async fn cbor() {
+    use crate::{Cbor, post};
+
+    let app = Router::new().at("/", post(async |Cbor(p): Cbor<Point>| Cbor(p.x + p.y)));
+    let mut body = Vec::new();
+    ciborium::into_writer(&Point { x: 1, y: 2 }, &mut body).unwrap();
+
+    let resp = send(&app, "application/cbor", body).await;
+    assert_eq!(resp.headers()[header::CONTENT_TYPE], "application/cbor");
+    let sum: i32 = ciborium::from_reader(&bytes(resp).await[..]).unwrap();
+    assert_eq!(sum, 3);
+
+    let resp = send(&app, "application/cbor", vec![0xa2]).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let app = app.with_state(crate::types::CborConfig {
+        content_types: crate::types::ContentTypes::new().with(mime::APPLICATION_OCTET_STREAM),
+    });
+    let mut body = Vec::new();
+    ciborium::into_writer(&Point { x: 1, y: 2 }, &mut body).unwrap();
+    let resp = send(&app, "application/octet-stream", body.clone()).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let resp = send(&app, "application/cbor", body).await;
+    assert_eq!(resp.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+}
+
+#[cfg(feature = "ndjson")]
+#[compio::test]
+async fn ndjson() {
+    use futures::StreamExt;
+
+    use crate::{NdJson, NdJsonStream, post};
+
+    async fn mirror(
+        NdJson(lines): NdJson<NdJsonStream<Point>>,
+    ) -> NdJson<impl futures::Stream<Item = String>> {
+        NdJson(lines.map(|line| match line {
+            Ok(p) => format!("{},{}", p.x, p.y),
+            Err(err) => err.status_code().to_string(),
+        }))
+    }
+
+    let app = Router::new().at("/", post(mirror));
+    let body = "{\"x\":1,\"y\":2}\n\n  \n{\"x\":3}\n{\"x\":5,\"y\":6}";
+    let resp = send(&app, "application/x-ndjson", body).await;
+    assert_eq!(resp.headers()[header::CONTENT_TYPE], "application/x-ndjson");
+    assert_eq!(
+        bytes(resp).await,
+        "\"1,2\"\n\"422 Unprocessable Entity\"\n\"5,6\"\n"
+    );
+
+    let resp = send(&app, "application/json", body).await;
+    assert_eq!(resp.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+}
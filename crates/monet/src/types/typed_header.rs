@@ -0,0 +1,209 @@
+use std::{cmp::Ordering, convert::Infallible};
+
+use async_trait::async_trait;
+use headers::{Header, HeaderMapExt};
+use http::{HeaderName, HeaderValue, header};
+use mime::Mime;
+
+use crate::{
+    error::Error,
+    extract::FromRequestParts,
+    request::Request,
+    response::{IntoResponse, Response},
+};
+
+/// A header parsed into `H`, e.g. `TypedHeader<Authorization<Bearer>>` or
+/// `TypedHeader<UserAgent>`. The types live in [`monet::headers`](crate::headers).
+///
+/// As an extractor, a missing or malformed header rejects the request with `400 Bad Request`;
+/// take an `Option<TypedHeader<H>>` when the header is optional. As a response, or as the first
+/// element of a `(TypedHeader<H>, R)` tuple, the header is added to the response.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TypedHeader<H>(pub H);
+
+impl<H> std::ops::Deref for TypedHeader<H> {
+    type Target = H;
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+impl<H> std::ops::DerefMut for TypedHeader<H> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+#[async_trait(?Send)]
+impl<H: Header> FromRequestParts for TypedHeader<H> {
+    type Rejection = Error;
+
+    async fn from_request_parts(req: &mut Request) -> Result<Self, Self::Rejection> {
+        let name = H::name().as_str();
+        match req.headers().typed_try_get::<H>() {
+            Ok(Some(value)) => Ok(TypedHeader(value)),
+            Ok(None) => Err(Error::MissingHeader(name)),
+            Err(err) => Err(Error::InvalidHeader {
+                name,
+                reason: err.to_string(),
+            }),
+        }
+    }
+}
+
+impl<H: Header> IntoResponse for TypedHeader<H> {
+    fn into_response(self) -> Response {
+        (self, ()).into_response()
+    }
+}
+
+impl<H: Header, R: IntoResponse> IntoResponse for (TypedHeader<H>, R) {
+    fn into_response(self) -> Response {
+        let mut resp = self.1.into_response();
+        resp.headers_mut().typed_insert(self.0.0);
+        resp
+    }
+}
+
+/// The `Accept` header: the media types a client accepts, most preferred first.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Accept(Vec<(Mime, f32)>);
+
+impl Accept {
+    /// Accept everything, like `*/*` or a missing header.
+    pub fn any() -> Self {
+        Self(vec![(mime::STAR_STAR, 1.0)])
+    }
+
+    /// The accepted media ranges, e.g. `text/*`, sorted by decreasing quality.
+    pub fn iter(&self) -> impl Iterator<Item = &Mime> {
+        self.0.iter().map(|(mime, _)| mime)
+    }
+
+    /// The quality of `mime` between 0 and 1, given by the most specific range matching it.
+    pub fn quality(&self, mime: &Mime) -> f32 {
+        self.0
+            .iter()
+            .filter_map(|(range, q)| {
+                let specificity = match (range.type_(), range.subtype()) {
+                    (mime::STAR, _) => 0,
+                    (ty, mime::STAR) if ty == mime.type_() => 1,
+                    (ty, sub) if ty == mime.type_() && sub == mime.subtype() => 2,
+                    _ => return None,
+                };
+                Some((specificity, *q))
+            })
+            .max_by_key(|(specificity, _)| *specificity)
+            .map_or(0.0, |(_, q)| q)
+    }
+
+    pub fn accepts(&self, mime: &Mime) -> bool {
+        self.quality(mime) > 0.0
+    }
+
+    /// The media type of `available` the client prefers, the first one on a tie.
+    pub fn preferred<'a>(&self, available: &'a [Mime]) -> Option<&'a Mime> {
+        available
+            .iter()
+            .map(|mime| (mime, self.quality(mime)))
+            .filter(|(_, q)| *q > 0.0)
+            .fold(None, |best: Option<(&Mime, f32)>, (mime, q)| match best {
+                Some((_, best_q)) if best_q >= q => best,
+                _ => Some((mime, q)),
+            })
+            .map(|(mime, _)| mime)
+    }
+}
+
+/// As an extractor, a missing or malformed header accepts everything.
+#[async_trait(?Send)]
+impl FromRequestParts for Accept {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(req: &mut Request) -> Result<Self, Self::Rejection> {
+        Ok(req.headers().typed_get().unwrap_or_else(Accept::any))
+    }
+}
+
+impl Header for Accept {
+    fn name() -> &'static HeaderName {
+        &header::ACCEPT
+    }
+
+    fn decode<'i, I>(values: &mut I) -> Result<Self, headers::Error>
+    where
+        I: Iterator<Item = &'i HeaderValue>,
+    {
+        let mut ranges = Vec::new();
+        for value in values {
+            let value = value.to_str().map_err(|_| headers::Error::invalid())?;
+            for range in value.split(',').map(str::trim).filter(|r| !r.is_empty()) {
+                let mime: Mime = range.parse().map_err(|_| headers::Error::invalid())?;
+                let quality = match mime.get_param("q") {
+                    Some(q) => q
+                        .as_str()
+                        .parse::<f32>()
+                        .ok()
+                        .filter(|q| (0.0..=1.0).contains(q))
+                        .ok_or_else(headers::Error::invalid)?,
+                    None => 1.0,
+                };
+                ranges.push((mime, quality));
+            }
+        }
+        ranges.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap_or(Ordering::Equal));
+        Ok(Accept(ranges))
+    }
+
+    fn encode<E: Extend<HeaderValue>>(&self, values: &mut E) {
+        let value = self.iter().map(Mime::as_ref).collect::<Vec<_>>().join(", ");
+        if let Ok(value) = HeaderValue::from_str(&value) {
+            values.extend(std::iter::once(value));
+        }
+    }
+}
+
+/// The `Last-Event-ID` header: the id of the last [`Event`] a client reconnecting to an
+/// [`Sse`] stream received, to resume from there.
+///
+/// As an extractor, a missing header rejects the request; take an `Option<LastEventId>` on
+/// routes also serving new clients.
+///
+/// [`Event`]: crate::response::Event
+/// [`Sse`]: crate::Sse
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LastEventId(pub String);
+
+static LAST_EVENT_ID: HeaderName = HeaderName::from_static("last-event-id");
+
+#[async_trait(?Send)]
+impl FromRequestParts for LastEventId {
+    type Rejection = Error;
+
+    async fn from_request_parts(req: &mut Request) -> Result<Self, Self::Rejection> {
+        let TypedHeader(id) = TypedHeader::from_request_parts(req).await?;
+        Ok(id)
+    }
+}
+
+impl Header for LastEventId {
+    fn name() -> &'static HeaderName {
+        &LAST_EVENT_ID
+    }
+
+    fn decode<'i, I>(values: &mut I) -> Result<Self, headers::Error>
+    where
+        I: Iterator<Item = &'i HeaderValue>,
+    {
+        let value = values.next().ok_or_else(headers::Error::invalid)?;
+        let id = value.to_str().map_err(|_| headers::Error::invalid())?;
+        Ok(LastEventId(id.to_string()))
+    }
+
+    fn encode<E: Extend<HeaderValue>>(&self, values: &mut E) {
+        if let Ok(value) = HeaderValue::from_str(&self.0) {
+            values.extend(std::iter::once(value));
+        }
+    }
+}
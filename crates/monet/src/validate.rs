@@ -0,0 +1,198 @@
+//! Validation of extracted payloads.
+//!
+//! Implement [`Validate`] for a payload type and extract it wrapped in [`Valid`]: the request
+//! is rejected with `422 Unprocessable Entity` and the message of every invalid field when
+//! validation fails, after the extraction itself succeeded.
+//!
+//! ```ignore
+//! impl Validate for NewUser {
+//!     fn validate(&self) -> Result<(), ValidationErrors> {
+//!         let mut errors = ValidationErrors::new();
+//!         if self.name.is_empty() {
+//!             errors.add("name", "must not be empty");
+//!         }
+//!         if self.age < 18 {
+//!             errors.add("age", "must be at least 18");
+//!         }
+//!         errors.into_result()
+//!     }
+//! }
+//!
+//! async fn create_user(Valid(Json(user)): Valid<Json<NewUser>>) -> StatusCode {
+//!     StatusCode::CREATED
+//! }
+//! ```
+//!
+//! The errors are also listed per field in the `errors` member of the problem details, see
+//! [`crate::problem`].
+
+#[cfg(test)]
+mod tests;
+
+use std::{collections::BTreeMap, fmt};
+
+use async_trait::async_trait;
+
+#[cfg(feature = "cbor")]
+use crate::types::Cbor;
+#[cfg(feature = "msgpack")]
+use crate::types::MsgPack;
+#[cfg(feature = "nested-query")]
+use crate::types::NestedQuery;
+use crate::{
+    error::Error,
+    extract::{FromRequest, FromRequestParts},
+    request::{Request, StateKey},
+    types::{Form, Json, Path, Query},
+};
+
+/// A value that can check its own invariants.
+pub trait Validate {
+    fn validate(&self) -> Result<(), ValidationErrors>;
+}
+
+/// The messages of every invalid field, by field name.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ValidationErrors {
+    fields: BTreeMap<String, Vec<String>>,
+}
+
+impl ValidationErrors {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Record that `field` is invalid. A field can have several messages.
+    pub fn add(&mut self, field: impl Into<String>, message: impl Into<String>) {
+        self.fields
+            .entry(field.into())
+            .or_default()
+            .push(message.into());
+    }
+
+    /// Add the errors of a nested value, with their fields prefixed by `field.`.
+    pub fn merge(&mut self, field: &str, nested: ValidationErrors) {
+        for (name, messages) in nested.fields {
+            self.fields
+                .entry(format!("{field}.{name}"))
+                .or_default()
+                .extend(messages);
+        }
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// The messages of every invalid field, sorted by field name.
+    pub fn fields(&self) -> &BTreeMap<String, Vec<String>> {
+        &self.fields
+    }
+
+    /// `Ok` when no error was added.
+    pub fn into_result(self) -> Result<(), Self> {
+        match self.is_empty() {
+            true => Ok(()),
+            false => Err(self),
+        }
+    }
+}
+
+impl fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (field, messages)) in self.fields.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "`{field}` {}", messages.join(" and "))?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ValidationErrors {}
+
+macro_rules! impl_validate {
+    ($($(#[$cfg:meta])* $ty:ident),*) => {
+        $(
+            $(#[$cfg])*
+            impl<T: Validate> Validate for $ty<T> {
+                fn validate(&self) -> Result<(), ValidationErrors> {
+                    self.0.validate()
+                }
+            }
+        )*
+    };
+}
+
+impl_validate!(
+    Json,
+    Form,
+    Query,
+    Path,
+    #[cfg(feature = "nested-query")]
+    NestedQuery,
+    #[cfg(feature = "msgpack")]
+    MsgPack,
+    #[cfg(feature = "cbor")]
+    Cbor
+);
+
+/// An extracted payload that passed [`Validate::validate`], e.g. `Valid<Json<T>>`.
+///
+/// Rejects with the error of the inner extractor, or [`Error::Validation`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Valid<E>(pub E);
+
+impl<E> std::ops::Deref for Valid<E> {
+    type Target = E;
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+impl<E> std::ops::DerefMut for Valid<E> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+#[async_trait(?Send)]
+impl<E> FromRequestParts for Valid<E>
+where
+    E: FromRequestParts + Validate,
+    E::Rejection: Into<Error>,
+{
+    type Rejection = Error;
+
+    async fn from_request_parts(req: &mut Request) -> Result<Self, Self::Rejection> {
+        let value = E::from_request_parts(req).await.map_err(Into::into)?;
+        value.validate().map_err(Error::Validation)?;
+        Ok(Valid(value))
+    }
+
+    fn required_state(keys: &mut Vec<StateKey>) {
+        E::required_state(keys);
+    }
+}
+
+#[async_trait(?Send)]
+impl<E> FromRequest for Valid<E>
+where
+    E: FromRequest + Validate,
+    E::Rejection: Into<Error>,
+{
+    type Rejection = Error;
+
+    async fn from_request(req: Request) -> Result<Self, Self::Rejection> {
+        let value = E::from_request(req).await.map_err(Into::into)?;
+        value.validate().map_err(Error::Validation)?;
+        Ok(Valid(value))
+    }
+
+    fn required_state(keys: &mut Vec<StateKey>) {
+        <E as FromRequest>::required_state(keys);
+    }
+}
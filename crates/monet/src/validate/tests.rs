@@ -0,0 +1,120 @@
+use http::{HeaderValue, Method, StatusCode, header};
+use http_body_util::{BodyExt, Full};
+use serde::Deserialize;
+use serde_json::json;
+
+use super::{Valid, Validate, ValidationErrors};
+use crate::{Json, ProblemJson, Query, Router, body::Body, post, router::tests::request};
+
+#[derive(Deserialize)]
+struct NewUser {
+    name: String,
+    age: u32,
+}
+
+impl Validate for NewUser {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        if self.name.is_empty() {
+            errors.add("name", "must not be empty");
+        }
+        if self.age < 18 {
+            errors.add("age", "must be at least 18");
+        }
+        errors.into_result()
+    }
+}
+
+#[derive(Deserialize)]
+struct Page {
+    size: u32,
+}
+
+impl Validate for Page {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        if self.size > 100 {
+            errors.add("size", "must be at most 100");
+        }
+        errors.into_result()
+    }
+}
+
+fn app() -> Router {
+    async fn create(Valid(Json(user)): Valid<Json<NewUser>>) -> String {
+        user.name
+    }
+    async fn list(Valid(Query(page)): Valid<Query<Page>>) -> String {
+        page.size.to_string()
+    }
+
+    Router::new()
+        .at("/users", post(create).get(list))
+        .wrap_by(ProblemJson::always())
+}
+
+async fn create(app: &Router, body: &'static str) -> (StatusCode, String) {
+    let mut req = request(Method::POST, "/users");
+    req.headers_mut().insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/json"),
+    );
+    req.body = Body::new(Full::new(body.into()));
+    let resp = app.handle(req).await;
+    let status = resp.status();
+    let body = resp.into_body().collect().await.unwrap().to_bytes();
+    (status, String::from_utf8(body.to_vec()).unwrap())
+}
+
+#[compio::test]
+async fn valid_json() {
+    let app = app();
+
+    let (status, body) = create(&app, r#"{"name": "ann", "age": 30}"#).await;
+    assert_eq!((status, body.as_str()), (StatusCode::OK, "ann"));
+
+    let (status, body) = create(&app, r#"{"name": "", "age": 3}"#).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    let problem: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(
+        problem["errors"],
+        json!({
+            "age": ["must be at least 18"],
+            "name": ["must not be empty"],
+        })
+    );
+    assert_eq!(
+        problem["detail"],
+        "Validation failed: `age` must be at least 18, `name` must not be empty"
+    );
+
+    // Deserialization errors come first, in the same format.
+    let (status, body) = create(&app, r#"{"name": "ann", "age": -1}"#).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    let problem: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(problem["field"], "age");
+}
+
+#[compio::test]
+async fn valid_query() {
+    let app = app();
+    let status = async |uri| app.handle(request(Method::GET, uri)).await.status();
+
+    assert_eq!(status("/users?size=10").await, StatusCode::OK);
+    assert_eq!(
+        status("/users?size=1000").await,
+        StatusCode::UNPROCESSABLE_ENTITY
+    );
+    assert_eq!(status("/users?size=x").await, StatusCode::BAD_REQUEST);
+}
+
+#[test]
+fn merge_nested_errors() {
+    let mut address = ValidationErrors::new();
+    address.add("zip", "must have 5 digits");
+    let mut errors = ValidationErrors::new();
+    errors.merge("address", address);
+    errors.merge("phone", ValidationErrors::new());
+
+    assert_eq!(errors.fields().keys().collect::<Vec<_>>(), ["address.zip"]);
+}